@@ -0,0 +1,128 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::GameError;
+
+pub const TRADER_SALES: u32 = 10;
+pub const DEVOTED_STREAK: u32 = 30;
+pub const MASTER_LEVEL: u32 = 10;
+
+/// Achievement badges, minted once per player as soulbound tokens.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum Badge {
+    FirstMix,
+    Trader,
+    Devoted,
+    Master,
+}
+
+const ALL: [Badge; 4] = [
+    Badge::FirstMix,
+    Badge::Trader,
+    Badge::Devoted,
+    Badge::Master,
+];
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Awarded(Address, Badge),
+    RecipeGate(u32),
+    EventGate(u32),
+}
+
+pub fn token_of(env: &Env, player: &Address, badge: Badge) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::Awarded(player.clone(), badge))
+}
+
+pub fn has(env: &Env, player: &Address, badge: Badge) -> bool {
+    token_of(env, player, badge).is_some()
+}
+
+pub fn mark_awarded(env: &Env, player: &Address, badge: Badge, token_id: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Awarded(player.clone(), badge), &token_id);
+}
+
+pub fn held(env: &Env, player: &Address) -> Vec<Badge> {
+    let mut badges = Vec::new(env);
+    for badge in ALL {
+        if has(env, player, badge) {
+            badges.push_back(badge);
+        }
+    }
+    badges
+}
+
+fn set_gate(env: &Env, key: DataKey, badge: Option<Badge>) {
+    match badge {
+        Some(badge) => env.storage().instance().set(&key, &badge),
+        None => env.storage().instance().remove(&key),
+    }
+}
+
+pub fn set_recipe_gate(env: &Env, recipe_id: u32, badge: Option<Badge>) {
+    set_gate(env, DataKey::RecipeGate(recipe_id), badge);
+}
+
+pub fn recipe_gate(env: &Env, recipe_id: u32) -> Option<Badge> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Badge>(&DataKey::RecipeGate(recipe_id))
+}
+
+pub fn set_event_gate(env: &Env, event_id: u32, badge: Option<Badge>) {
+    set_gate(env, DataKey::EventGate(event_id), badge);
+}
+
+pub fn event_gate(env: &Env, event_id: u32) -> Option<Badge> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Badge>(&DataKey::EventGate(event_id))
+}
+
+pub fn ensure_holds(env: &Env, player: &Address, gate: Option<Badge>) -> Result<(), GameError> {
+    match gate {
+        Some(badge) if !has(env, player, badge) => Err(GameError::Unauthorized),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Address as _, Address, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn gates_follow_awarded_badges() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            set_recipe_gate(&env, 3, Some(Badge::Trader));
+            assert_eq!(
+                ensure_holds(&env, &player, recipe_gate(&env, 3)),
+                Err(GameError::Unauthorized)
+            );
+            assert!(ensure_holds(&env, &player, recipe_gate(&env, 4)).is_ok());
+
+            mark_awarded(&env, &player, Badge::Trader, 42);
+            assert!(ensure_holds(&env, &player, recipe_gate(&env, 3)).is_ok());
+            assert_eq!(held(&env, &player).len(), 1);
+            assert_eq!(token_of(&env, &player, Badge::Trader), Some(42));
+
+            set_recipe_gate(&env, 3, None);
+            assert!(recipe_gate(&env, 3).is_none());
+        });
+    }
+}
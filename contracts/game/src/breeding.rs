@@ -0,0 +1,139 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::errors::GameError;
+
+/// Cooldown for a founder tea; each generation adds another period.
+pub const BASE_COOLDOWN: u64 = 3_600;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BreedRecord {
+    pub generation: u32,
+    pub breed_count: u32,
+    pub cooldown_until: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Record(u64),
+    MaxBreeds(u32),
+}
+
+pub fn record(env: &Env, token_id: u64) -> BreedRecord {
+    env.storage()
+        .persistent()
+        .get::<DataKey, BreedRecord>(&DataKey::Record(token_id))
+        .unwrap_or(BreedRecord {
+            generation: 0,
+            breed_count: 0,
+            cooldown_until: 0,
+        })
+}
+
+fn put(env: &Env, token_id: u64, record: &BreedRecord) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Record(token_id), record);
+}
+
+pub fn clear(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Record(token_id));
+}
+
+pub fn cooldown_for(generation: u32) -> u64 {
+    BASE_COOLDOWN * (generation as u64 + 1)
+}
+
+pub fn set_max_breeds(env: &Env, recipe_id: u32, max_breeds: u32) {
+    if max_breeds == 0 {
+        env.storage()
+            .instance()
+            .remove(&DataKey::MaxBreeds(recipe_id));
+    } else {
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxBreeds(recipe_id), &max_breeds);
+    }
+}
+
+pub fn max_breeds(env: &Env, recipe_id: u32) -> Option<u32> {
+    env.storage()
+        .instance()
+        .get::<DataKey, u32>(&DataKey::MaxBreeds(recipe_id))
+}
+
+pub fn ensure_can_breed(env: &Env, token_id: u64, recipe_id: u32) -> Result<(), GameError> {
+    let record = record(env, token_id);
+    if env.ledger().timestamp() < record.cooldown_until {
+        return Err(GameError::NotReady);
+    }
+    if let Some(max) = max_breeds(env, recipe_id) {
+        if record.breed_count >= max {
+            return Err(GameError::LimitExceeded);
+        }
+    }
+    Ok(())
+}
+
+/// Counts a breed against a surviving parent and starts its cooldown.
+pub fn record_breed(env: &Env, token_id: u64, generation: u32) {
+    let mut record = record(env, token_id);
+    record.generation = generation;
+    record.breed_count += 1;
+    record.cooldown_until = env.ledger().timestamp() + cooldown_for(generation);
+    put(env, token_id, &record);
+}
+
+/// Registers a freshly minted child. It starts cooling down immediately.
+pub fn register_child(env: &Env, token_id: u64, generation: u32) {
+    let record = BreedRecord {
+        generation,
+        breed_count: 0,
+        cooldown_until: env.ledger().timestamp() + cooldown_for(generation),
+    };
+    put(env, token_id, &record);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Ledger, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn cooldown_and_cap_refuse_breeding() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            env.ledger().set_timestamp(100);
+            register_child(&env, 1, 2);
+            assert_eq!(record(&env, 1).cooldown_until, 100 + 3 * BASE_COOLDOWN);
+            assert_eq!(ensure_can_breed(&env, 1, 5), Err(GameError::NotReady));
+
+            env.ledger().set_timestamp(100 + 3 * BASE_COOLDOWN);
+            ensure_can_breed(&env, 1, 5).unwrap();
+            set_max_breeds(&env, 5, 1);
+            record_breed(&env, 1, 2);
+            let breed = record(&env, 1);
+            assert_eq!((breed.generation, breed.breed_count), (2, 1));
+
+            env.ledger().set_timestamp(100 + 6 * BASE_COOLDOWN);
+            assert_eq!(ensure_can_breed(&env, 1, 5), Err(GameError::LimitExceeded));
+            ensure_can_breed(&env, 1, 6).unwrap();
+            set_max_breeds(&env, 5, 0);
+            ensure_can_breed(&env, 1, 5).unwrap();
+
+            clear(&env, 1);
+            assert_eq!(record(&env, 1).breed_count, 0);
+        });
+    }
+}
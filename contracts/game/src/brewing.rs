@@ -0,0 +1,119 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::GameError;
+
+pub const MAX_INGREDIENTS: u32 = 8;
+/// BALLS burned for every second a brew is sped up.
+pub const SPEED_UP_PRICE_PER_SECOND: i128 = 1_000;
+
+/// A timed brew holding its ingredients in escrow. `balls_paid` is the BALLS cost
+/// charged when it started.
+#[derive(Clone)]
+#[contracttype]
+pub struct Brew {
+    pub brewer: Address,
+    pub recipe_id: u32,
+    pub ingredients: Vec<u64>,
+    pub balls_paid: i128,
+    pub started_at: u64,
+    pub ready_at: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    BrewTime(u32),
+    Brew(u64),
+    Sequence,
+}
+
+pub fn set_brew_time(env: &Env, recipe_id: u32, duration: u64) {
+    if duration == 0 {
+        env.storage()
+            .instance()
+            .remove(&DataKey::BrewTime(recipe_id));
+    } else {
+        env.storage()
+            .instance()
+            .set(&DataKey::BrewTime(recipe_id), &duration);
+    }
+}
+
+pub fn brew_time(env: &Env, recipe_id: u32) -> Option<u64> {
+    env.storage()
+        .instance()
+        .get::<DataKey, u64>(&DataKey::BrewTime(recipe_id))
+}
+
+pub fn next_id(env: &Env) -> u64 {
+    let mut seq = env
+        .storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::Sequence)
+        .unwrap_or(0);
+    seq += 1;
+    env.storage().persistent().set(&DataKey::Sequence, &seq);
+    seq
+}
+
+pub fn put(env: &Env, id: u64, brew: &Brew) {
+    env.storage().persistent().set(&DataKey::Brew(id), brew);
+}
+
+pub fn get(env: &Env, id: u64) -> Result<Brew, GameError> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Brew>(&DataKey::Brew(id))
+        .ok_or(GameError::OfferNotFound)
+}
+
+pub fn remove(env: &Env, id: u64) {
+    env.storage().persistent().remove(&DataKey::Brew(id));
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Address as _, vec, Address, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn brews_are_stored_under_fresh_ids() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            set_brew_time(&env, 1, 600);
+            assert_eq!(brew_time(&env, 1), Some(600));
+            set_brew_time(&env, 1, 0);
+            assert_eq!(brew_time(&env, 1), None);
+
+            let brew_id = next_id(&env);
+            assert_eq!(next_id(&env), brew_id + 1);
+            let brewer = Address::generate(&env);
+            put(
+                &env,
+                brew_id,
+                &Brew {
+                    brewer: brewer.clone(),
+                    recipe_id: 1,
+                    ingredients: vec![&env, 3, 4],
+                    balls_paid: 0,
+                    started_at: 0,
+                    ready_at: 600,
+                },
+            );
+            let brew = get(&env, brew_id).unwrap();
+            assert_eq!((brew.brewer, brew.ready_at), (brewer, 600));
+            assert_eq!(brew.ingredients, vec![&env, 3, 4]);
+
+            remove(&env, brew_id);
+            assert!(matches!(get(&env, brew_id), Err(GameError::OfferNotFound)));
+        });
+    }
+}
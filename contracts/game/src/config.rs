@@ -15,9 +15,11 @@ pub struct Config {
 #[contracttype]
 pub enum DataKey {
     Config,
-    DailyEmissionCap,
+    MaxOffersPerOwner,
 }
 
+pub const DEFAULT_MAX_OFFERS_PER_OWNER: u32 = 5;
+
 pub fn init(
     env: &Env,
     admin: &Address,
@@ -59,15 +61,16 @@ pub fn update_treasury(env: &Env, treasury: Address) {
     env.events().publish(("treasury_updated",), (treasury,));
 }
 
-pub fn set_daily_cap(env: &Env, amount: i128) {
+pub fn set_max_offers_per_owner(env: &Env, cap: u32) {
     require_admin(env);
     env.storage()
         .instance()
-        .set(&DataKey::DailyEmissionCap, &amount);
+        .set(&DataKey::MaxOffersPerOwner, &cap);
 }
 
-pub fn daily_cap(env: &Env) -> Option<i128> {
+pub fn max_offers_per_owner(env: &Env) -> u32 {
     env.storage()
         .instance()
-        .get::<DataKey, i128>(&DataKey::DailyEmissionCap)
+        .get::<DataKey, u32>(&DataKey::MaxOffersPerOwner)
+        .unwrap_or(DEFAULT_MAX_OFFERS_PER_OWNER)
 }
@@ -1,15 +1,34 @@
 use soroban_sdk::xdr::ToXdr;
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, String, Symbol, Vec};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, vec, Address, BytesN, Env, String, Symbol, Vec,
+};
 
 use crate::{
+    badges::{self, Badge},
+    breeding::{self, BreedRecord},
+    brewing::{self, Brew},
     config,
+    emission::{self, EmissionBudget, EmissionSchedule},
     errors::GameError,
-    events, limits,
+    events, experience,
+    freshness::{self, FreshnessConfig},
+    genealogy::{self, Pedigree},
+    history::{self, HistoryEntry, HistoryKind},
+    limits,
     marketplace::{self, Listing, PaymentToken},
-    mixing::{self, BeverageMixer, MixOffer, OfferStatus},
-    rewards,
+    mixing::{
+        self, BeverageMixer, FeeDistribution, MixBid, MixMode, MixOffer, MixReceipt, MixRules,
+        OfferMode, OfferStatus,
+    },
+    profiles::{self, LeaderboardEntry, LeaderboardMetric, PlayerProfile},
+    quests::{self, Quest, QuestAction, QuestProgress},
+    referrals::{self, ReferralStats},
+    rewards::{self, StreakConfig, StreakStatus},
+    salvage,
+    seasons::{self, Season},
+    sessions::{self, Contribution, IngredientSlot, MixSession, SessionStatus},
     tea::{TeaMetadata, TeaStats},
-    util,
+    util, vouchers,
 };
 
 const MARKET_FEE_BPS: i128 = 300; // 3%
@@ -19,9 +38,14 @@ const TREASURY_REWARD_PERCENT: i128 = 20;
 const UPGRADE_LEVEL_INCREMENT: u32 = 1;
 const DAILY_BALLS_REWARD: i128 = 2_000_000; // 0.02 with 8 decimals
 const DAILY_STARS_REWARD: i128 = 200_000; // 0.002 with 8 decimals
+const COOP_STAT_VARIANCE: u32 = 2;
+const KEEPER_REWARD_BPS: i128 = 100; // 1% of pooled mix fees
+const DEFERRED_WITHDRAWAL_WINDOW: u64 = 3_600;
+const DEFERRED_FORCE_TIMEOUT: u64 = 86_400;
 
 struct MixOutcome {
     new_token_id: u64,
+    partner_token_id: Option<u64>,
     winner: Address,
     loser: Address,
     total_balls: i128,
@@ -42,10 +66,35 @@ pub struct Recipe {
     pub image_uri: String,
 }
 
+/// Parent conditions a recipe places on a two-tea mix. The two flavor profiles may be
+/// satisfied by the parents in either order; the infusion by either parent.
+#[derive(Clone)]
+#[soroban_sdk::contracttype]
+pub struct RecipeRequirements {
+    pub profile_a: Option<String>,
+    pub profile_b: Option<String>,
+    pub infusion: Option<String>,
+    pub min_level: u32,
+}
+
+#[derive(Clone)]
+#[soroban_sdk::contracttype]
+pub struct Discovery {
+    pub discoverer: Address,
+    pub discovered_at: u64,
+}
+
 #[derive(Clone)]
 #[soroban_sdk::contracttype]
 enum DataKey {
     Recipe(u32),
+    RecipeIds,
+    MixRules(u32),
+    Requirements(u32),
+    Hidden(u32),
+    HiddenIds,
+    Discovery(u32),
+    DiscoveryReward,
 }
 
 fn get_recipe(env: &Env, recipe_id: u32) -> Result<Recipe, GameError> {
@@ -56,34 +105,518 @@ fn get_recipe(env: &Env, recipe_id: u32) -> Result<Recipe, GameError> {
 }
 
 fn put_recipe(env: &Env, recipe: &Recipe) {
+    let mut ids = recipe_ids(env);
+    if !ids.contains(recipe.id) {
+        ids.push_back(recipe.id);
+        env.storage().instance().set(&DataKey::RecipeIds, &ids);
+    }
     env.storage()
         .instance()
         .set(&DataKey::Recipe(recipe.id), recipe);
 }
 
+/// Looks up a recipe players may select. Hidden recipes stay unknown until discovered.
+fn get_playable_recipe(env: &Env, recipe_id: u32) -> Result<Recipe, GameError> {
+    if is_locked(env, recipe_id) {
+        return Err(GameError::OfferNotFound);
+    }
+    get_recipe(env, recipe_id)
+}
+
+fn is_locked(env: &Env, recipe_id: u32) -> bool {
+    let hidden = env
+        .storage()
+        .instance()
+        .get::<DataKey, bool>(&DataKey::Hidden(recipe_id))
+        .unwrap_or(false);
+    hidden && get_discovery(env, recipe_id).is_none()
+}
+
+/// Hidden recipes still waiting for their discoverer, so mixes only scan those.
+fn hidden_ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Vec<u32>>(&DataKey::HiddenIds)
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_hidden_listed(env: &Env, recipe_id: u32, listed: bool) {
+    let mut ids = hidden_ids(env);
+    match (ids.first_index_of(recipe_id), listed) {
+        (None, true) => ids.push_back(recipe_id),
+        (Some(index), false) => {
+            ids.remove(index);
+        }
+        _ => return,
+    }
+    env.storage().instance().set(&DataKey::HiddenIds, &ids);
+}
+
+fn get_discovery(env: &Env, recipe_id: u32) -> Option<Discovery> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Discovery>(&DataKey::Discovery(recipe_id))
+}
+
+/// Unlocks the first hidden recipe whose requirements the parents satisfy, paying the
+/// one-time discovery bonus to the player who produces it.
+fn discover_hidden(
+    env: &Env,
+    cfg: &config::Config,
+    parents: &Vec<TeaMetadata>,
+    player: &Address,
+) -> Option<Recipe> {
+    for recipe_id in hidden_ids(env).iter() {
+        let Some(requirements) = get_requirements(env, recipe_id) else {
+            continue;
+        };
+        if !requirements_met(&requirements, parents) {
+            continue;
+        }
+        let Ok(recipe) = get_recipe(env, recipe_id) else {
+            continue;
+        };
+
+        let discovery = Discovery {
+            discoverer: player.clone(),
+            discovered_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Discovery(recipe_id), &discovery);
+        set_hidden_listed(env, recipe_id, false);
+        let (bonus_balls, bonus_stars) = env
+            .storage()
+            .instance()
+            .get::<DataKey, (i128, i128)>(&DataKey::DiscoveryReward)
+            .unwrap_or((0, 0));
+        let (bonus_balls, bonus_stars) =
+            emission::allocate(env, bonus_balls, bonus_stars, false).unwrap_or((0, 0));
+        mint_rewards(env, cfg, player, bonus_balls, bonus_stars);
+        env.events().publish(
+            ("recipe_discovered",),
+            (recipe_id, player.clone(), bonus_balls, bonus_stars),
+        );
+        return Some(recipe);
+    }
+    None
+}
+
+fn recipe_ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Vec<u32>>(&DataKey::RecipeIds)
+        .unwrap_or(Vec::new(env))
+}
+
+fn get_requirements(env: &Env, recipe_id: u32) -> Option<RecipeRequirements> {
+    env.storage()
+        .instance()
+        .get::<DataKey, RecipeRequirements>(&DataKey::Requirements(recipe_id))
+}
+
+fn profile_matches(expected: &Option<String>, metadata: &TeaMetadata) -> bool {
+    match expected {
+        Some(profile) => *profile == metadata.flavor_profile,
+        None => true,
+    }
+}
+
+/// Every ingredient must reach the minimum level, the two profiles must be covered by
+/// different ingredients (a lone ingredient has to cover both) and any ingredient may
+/// carry the infusion.
+fn requirements_met(requirements: &RecipeRequirements, parents: &Vec<TeaMetadata>) -> bool {
+    if parents
+        .iter()
+        .any(|parent| parent.level < requirements.min_level)
+    {
+        return false;
+    }
+    let count = parents.len();
+    let profiles_covered = (0..count).any(|a| {
+        (0..count).any(|b| {
+            (a != b || count == 1)
+                && profile_matches(&requirements.profile_a, &parents.get_unchecked(a))
+                && profile_matches(&requirements.profile_b, &parents.get_unchecked(b))
+        })
+    });
+    if !profiles_covered {
+        return false;
+    }
+    match &requirements.infusion {
+        Some(infusion) => parents.iter().any(|parent| parent.infusion == *infusion),
+        None => true,
+    }
+}
+
+fn ensure_recipe_accepts(
+    env: &Env,
+    recipe_id: u32,
+    parents: &Vec<TeaMetadata>,
+) -> Result<(), GameError> {
+    match get_requirements(env, recipe_id) {
+        Some(requirements) if !requirements_met(&requirements, parents) => {
+            Err(GameError::InvalidInput)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn get_mix_rules(env: &Env, recipe_id: u32) -> MixRules {
+    env.storage()
+        .instance()
+        .get::<DataKey, MixRules>(&DataKey::MixRules(recipe_id))
+        .unwrap_or(MixRules::standard())
+}
+
 fn ensure_authorized_player(_env: &Env, player: &Address) -> Result<(), GameError> {
     player.require_auth();
     Ok(())
 }
 
-fn compose_metadata(env: &Env, recipe: &Recipe, offer: &MixOffer) -> TeaMetadata {
+fn offer_parents(env: &Env, offer: &MixOffer) -> Vec<u64> {
     let mut lineage = Vec::new(env);
     lineage.push_back(offer.token_a_id);
     if let Some(token_b) = offer.token_b_id {
         lineage.push_back(token_b);
     }
+    lineage
+}
+
+/// Builds a child's metadata from its recipe. Stale parents pass on weaker stats: the
+/// recipe's base stats are scaled by the parents' average freshness.
+fn compose_metadata(
+    env: &Env,
+    recipe: &Recipe,
+    lineage: Vec<u64>,
+    flavor_profile: String,
+) -> TeaMetadata {
+    let freshness = freshness::average(env, lineage.iter());
     TeaMetadata {
         display_name: recipe.name.clone(),
-        flavor_profile: offer.desired_profile.clone(),
+        flavor_profile,
         rarity: recipe.base_rarity,
         level: recipe.base_level,
         infusion: String::from_str(env, "fusion"),
-        stats: recipe.base_stats.clone(),
+        stats: freshness::scale(&recipe.base_stats, freshness),
         lineage,
         image_uri: recipe.image_uri.clone(),
     }
 }
 
+/// Whether a tea meets the partner criteria an offer owner asked for. An empty
+/// `desired_profile` accepts any flavor. Every path that pairs a partner tea with an
+/// offer applies it.
+fn offer_accepts(offer: &MixOffer, metadata: &TeaMetadata) -> bool {
+    if metadata.rarity < offer.min_rank {
+        return false;
+    }
+    offer.desired_profile.is_empty() || offer.desired_profile == metadata.flavor_profile
+}
+
+/// When a tea's freshness clock started: its last refresh, or its mint time for teas
+/// the game has not handled yet.
+fn freshness_since(env: &Env, cfg: &config::Config, token_id: u64) -> u64 {
+    freshness::refreshed_at(env, token_id)
+        .or_else(|| util::tea_minted_at(env, &cfg.tea_nft, token_id))
+        .unwrap_or(env.ledger().timestamp())
+}
+
+fn track_freshness(env: &Env, cfg: &config::Config, token_id: u64) {
+    freshness::ensure_tracked(env, token_id, freshness_since(env, cfg, token_id));
+}
+
+/// Records pedigree and breeding state once a mix resolves. Children sit one generation
+/// above their highest-generation parent; surviving parents count a breed and cool down.
+fn record_offspring(
+    env: &Env,
+    recipe_id: u32,
+    parents: &Vec<u64>,
+    parents_survive: bool,
+    children: &[(u64, &TeaMetadata)],
+) {
+    let mut generation = 0;
+    for parent in parents.iter() {
+        let parent_generation = genealogy::generation(env, parent);
+        generation = generation.max(parent_generation + 1);
+        if parents_survive {
+            breeding::record_breed(env, parent, parent_generation);
+        } else {
+            breeding::clear(env, parent);
+            freshness::clear(env, parent);
+            experience::clear(env, parent);
+            salvage::clear(env, parent);
+        }
+    }
+    for (child, metadata) in children {
+        genealogy::record_child(env, *child, parents, generation, recipe_id, metadata);
+        breeding::register_child(env, *child, generation);
+        freshness::touch(env, *child);
+    }
+}
+
+/// Collects a single-player charge. Without a loser to compensate, the compensation
+/// share of the fee split is burned and the rest goes to the treasury.
+fn collect_charge(env: &Env, cfg: &config::Config, token: &Address, payer: &Address, cost: i128) {
+    if cost <= 0 {
+        return;
+    }
+    let contract_address = env.current_contract_address();
+    util::transfer_from(env, token, payer, &contract_address, cost);
+    let (burn_share, treasury_share) = StellarTeaGame::split_fee(cost);
+    if burn_share > 0 {
+        util::burn(env, token, &contract_address, burn_share);
+    }
+    if treasury_share > 0 {
+        util::transfer(env, token, &contract_address, &cfg.treasury, treasury_share);
+    }
+}
+
+fn market_fee(price: i128) -> i128 {
+    price * MARKET_FEE_BPS / 10_000
+}
+
+/// Records a player action in their history. Mixes, purchases and daily claims also
+/// pay the player's referrer a bonus: on the treasury share of mix fees, on the market
+/// fee for purchases and on the amount claimed for daily claims.
+fn record_activity(
+    env: &Env,
+    cfg: &config::Config,
+    player: &Address,
+    kind: HistoryKind,
+    token_id: Option<u64>,
+    amount_balls: i128,
+    amount_stars: i128,
+) {
+    history::record(
+        env,
+        player,
+        kind.clone(),
+        token_id,
+        amount_balls,
+        amount_stars,
+    );
+    if !matches!(
+        kind,
+        HistoryKind::Mix | HistoryKind::Purchase | HistoryKind::Claim
+    ) {
+        return;
+    }
+    let Some(referrer) = referrals::referrer(env, player) else {
+        return;
+    };
+    let (basis_balls, basis_stars) = match kind {
+        HistoryKind::Purchase => (market_fee(amount_balls), market_fee(amount_stars)),
+        HistoryKind::Mix => (
+            StellarTeaGame::split_fee(amount_balls).1,
+            StellarTeaGame::split_fee(amount_stars).1,
+        ),
+        _ => (amount_balls, amount_stars),
+    };
+    let Ok((balls, stars)) = emission::allocate(
+        env,
+        referrals::bonus(env, basis_balls),
+        referrals::bonus(env, basis_stars),
+        false,
+    ) else {
+        return;
+    };
+    if balls == 0 && stars == 0 {
+        return;
+    }
+    mint_rewards(env, cfg, &referrer, balls, stars);
+    referrals::record_reward(env, &referrer, balls, stars);
+    env.events().publish(
+        ("referral_rewarded",),
+        (referrer, player.clone(), balls, stars),
+    );
+}
+
+/// Feeds a player action to profile XP, season points and quest progress, and awards
+/// any milestone badge it earns.
+fn track_progress(env: &Env, cfg: &config::Config, player: &Address, action: QuestAction) {
+    let xp = match &action {
+        QuestAction::Mix(_) => profiles::MIX_XP,
+        QuestAction::Sell | QuestAction::Purchase => profiles::TRADE_XP,
+        QuestAction::Upgrade(_) => profiles::UPGRADE_XP,
+        QuestAction::Claim => profiles::CLAIM_XP,
+    };
+    let profile = profiles::update(env, player, xp, |profile| match &action {
+        QuestAction::Mix(_) => profile.mixes += 1,
+        QuestAction::Sell => profile.sales += 1,
+        _ => {}
+    });
+    let earned = match &action {
+        QuestAction::Mix(_) => Some(Badge::FirstMix),
+        QuestAction::Sell if profile.sales >= badges::TRADER_SALES => Some(Badge::Trader),
+        QuestAction::Claim if rewards::streak(env, player).count >= badges::DEVOTED_STREAK => {
+            Some(Badge::Devoted)
+        }
+        QuestAction::Upgrade(level) if *level >= badges::MASTER_LEVEL => Some(Badge::Master),
+        _ => None,
+    };
+    let points = match &action {
+        QuestAction::Mix(_) => seasons::MIX_POINTS,
+        QuestAction::Upgrade(_) => seasons::UPGRADE_POINTS,
+        QuestAction::Claim => seasons::CLAIM_POINTS,
+        QuestAction::Sell | QuestAction::Purchase => 0,
+    };
+    seasons::add_points(env, player, points);
+    quests::record(env, player, action);
+    if let Some(badge) = earned {
+        award_badge(env, cfg, player, badge);
+    }
+}
+
+fn award_badge(env: &Env, cfg: &config::Config, player: &Address, badge: Badge) {
+    if badges::has(env, player, badge) {
+        return;
+    }
+    let name = match badge {
+        Badge::FirstMix => "First Mix",
+        Badge::Trader => "Trader",
+        Badge::Devoted => "Devoted",
+        Badge::Master => "Tea Master",
+    };
+    let metadata = TeaMetadata {
+        display_name: String::from_str(env, name),
+        flavor_profile: String::from_str(env, "badge"),
+        rarity: 0,
+        level: 0,
+        infusion: String::from_str(env, "soulbound"),
+        stats: TeaStats {
+            sweetness: 0,
+            body: 0,
+            caffeine: 0,
+        },
+        lineage: Vec::new(env),
+        image_uri: String::from_str(env, ""),
+    };
+    let token_id = util::mint_soulbound(env, &cfg.tea_nft, player, metadata);
+    badges::mark_awarded(env, player, badge, token_id);
+    env.events()
+        .publish(("badge_awarded",), (player.clone(), badge, token_id));
+}
+
+/// Owning a tea at the master level earns the Master badge, whether it was upgraded,
+/// minted or bought there.
+fn award_master_at(env: &Env, cfg: &config::Config, player: &Address, level: u32) {
+    if level >= badges::MASTER_LEVEL {
+        award_badge(env, cfg, player, Badge::Master);
+    }
+}
+
+fn mint_tea(env: &Env, cfg: &config::Config, to: &Address, metadata: TeaMetadata) -> u64 {
+    let level = metadata.level;
+    let token_id = util::mint_tea(env, &cfg.tea_nft, to, metadata);
+    award_master_at(env, cfg, to, level);
+    token_id
+}
+
+/// Mints reward tokens already allocated from the emission budget.
+fn mint_rewards(env: &Env, cfg: &config::Config, to: &Address, balls: i128, stars: i128) {
+    if balls > 0 {
+        util::mint(env, &cfg.balls_token, to, balls);
+    }
+    if stars > 0 {
+        util::mint(env, &cfg.stars_token, to, stars);
+    }
+}
+
+/// Raises a tea by one level. Paid upgrades and XP level-ups share the same bumps.
+fn apply_level_up(metadata: &mut TeaMetadata) {
+    metadata.level += UPGRADE_LEVEL_INCREMENT;
+    metadata.rarity += 1;
+    metadata.stats.body += 5;
+    metadata.stats.caffeine += 3;
+    metadata.stats.sweetness += 2;
+}
+
+/// Charges a recipe's cost for single-player crafting.
+fn charge_recipe_cost(env: &Env, cfg: &config::Config, payer: &Address, recipe: &Recipe) {
+    collect_charge(env, cfg, &cfg.balls_token, payer, recipe.balls_cost);
+    collect_charge(env, cfg, &cfg.stars_token, payer, recipe.stars_cost);
+}
+
+fn refund_bid(env: &Env, cfg: &config::Config, offer_id: u64, bid: &MixBid) {
+    let contract_address = env.current_contract_address();
+    util::transfer_tea(
+        env,
+        &cfg.tea_nft,
+        &contract_address,
+        &bid.bidder,
+        bid.token_id,
+    );
+    if bid.fee_balls > 0 {
+        util::transfer(
+            env,
+            &cfg.balls_token,
+            &contract_address,
+            &bid.bidder,
+            bid.fee_balls,
+        );
+    }
+    if bid.fee_stars > 0 {
+        util::transfer(
+            env,
+            &cfg.stars_token,
+            &contract_address,
+            &bid.bidder,
+            bid.fee_stars,
+        );
+    }
+    env.events()
+        .publish(("mix_bid_refunded",), (offer_id, bid.bidder.clone()));
+}
+
+fn vary_stat(value: u32, roll: u8) -> u32 {
+    let spread = 2 * COOP_STAT_VARIANCE + 1;
+    let offset = roll as u32 % spread;
+    if offset < COOP_STAT_VARIANCE {
+        value.saturating_sub(COOP_STAT_VARIANCE - offset)
+    } else {
+        value.saturating_add(offset - COOP_STAT_VARIANCE)
+    }
+}
+
+fn vary_stats(stats: &TeaStats, rolls: &[u8]) -> TeaStats {
+    TeaStats {
+        sweetness: vary_stat(stats.sweetness, rolls[0]),
+        body: vary_stat(stats.body, rolls[1]),
+        caffeine: vary_stat(stats.caffeine, rolls[2]),
+    }
+}
+
+fn mix_seed(env: &Env, offer: &MixOffer, partner: &Address, token_b_id: u64) -> [u8; 32] {
+    let payload = (
+        env.ledger().timestamp(),
+        offer.owner_a.clone(),
+        partner.clone(),
+        offer.token_a_id,
+        token_b_id,
+        offer.recipe_id,
+    )
+        .to_xdr(env);
+    env.crypto().sha256(&payload).to_array()
+}
+
+fn session_winner(env: &Env, session_id: u64, session: &MixSession) -> u32 {
+    let mut token_ids = Vec::new(env);
+    for contribution in session.contributions.iter() {
+        token_ids.push_back(contribution.token_id);
+    }
+    let payload = (
+        env.ledger().timestamp(),
+        session_id,
+        session.recipe_id,
+        token_ids,
+    )
+        .to_xdr(env);
+    let seed = env.crypto().sha256(&payload).to_array();
+    let roll = u32::from_be_bytes([seed[0], seed[1], seed[2], seed[3]]);
+    roll % session.contributions.len()
+}
+
 impl mixing::BeverageMixer for StellarTeaGame {
     fn decide_winner(
         env: &Env,
@@ -91,17 +624,7 @@ impl mixing::BeverageMixer for StellarTeaGame {
         token_b_id: u64,
     ) -> Result<(Address, Address), GameError> {
         let partner = offer.owner_b.clone().ok_or(GameError::NotReady)?;
-        let payload = (
-            env.ledger().timestamp(),
-            offer.owner_a.clone(),
-            partner.clone(),
-            offer.token_a_id,
-            token_b_id,
-            offer.recipe_id,
-        )
-            .to_xdr(env);
-        let seed = env.crypto().sha256(&payload);
-        let seed_bytes = seed.to_array();
+        let seed_bytes = mix_seed(env, offer, &partner, token_b_id);
         let owner_wins = seed_bytes[0] & 1 == 0;
         if owner_wins {
             Ok((offer.owner_a.clone(), partner))
@@ -122,6 +645,15 @@ fn assert_payment(amount: i128) -> Result<(), GameError> {
     Ok(())
 }
 
+/// Badges share the tea collection but are not game pieces; every entrypoint that
+/// takes a tea id refuses them.
+fn ensure_playable_tea(env: &Env, cfg: &config::Config, token_id: u64) -> Result<(), GameError> {
+    if util::is_soulbound(env, &cfg.tea_nft, token_id) {
+        return Err(GameError::InvalidInput);
+    }
+    Ok(())
+}
+
 fn ensure_fee_schedule(fee_balls: i128, fee_stars: i128) -> Result<(), GameError> {
     if fee_balls < 0 || fee_stars < 0 {
         return Err(GameError::InvalidInput);
@@ -137,131 +669,397 @@ pub struct StellarTeaGame;
 
 #[contractimpl]
 impl StellarTeaGame {
-    fn resolve_mix(env: Env, offer_id: u64, mut offer: MixOffer) -> Result<MixOutcome, GameError> {
+    fn resolve_mix(
+        env: Env,
+        offer_id: u64,
+        mut offer: MixOffer,
+        keeper: Option<Address>,
+    ) -> Result<MixOutcome, GameError> {
         let cfg = config::get(&env);
         let owner = offer.owner_a.clone();
+        let partner = offer.owner_b.clone().ok_or(GameError::NotReady)?;
         let token_b_id = offer.token_b_id.ok_or(GameError::NotReady)?;
         let recipe = get_recipe(&env, offer.recipe_id)?;
-        let (winner, loser) = StellarTeaGame::decide_winner(&env, &offer, token_b_id)?;
+        let rules = get_mix_rules(&env, offer.recipe_id);
+        let cooperative = rules.mode == MixMode::Cooperative;
+        let contract_address = env.current_contract_address();
+        let seed = mix_seed(&env, &offer, &partner, token_b_id);
+
+        // Cooperative mixes have no loser: the owner and partner each receive a child.
+        let (winner, loser) = if cooperative {
+            (owner.clone(), partner.clone())
+        } else {
+            StellarTeaGame::decide_winner(&env, &offer, token_b_id)?
+        };
 
-        util::burn_tea(
+        let parent_a = util::get_tea_metadata(&env, &cfg.tea_nft, offer.token_a_id);
+        let parent_b = util::get_tea_metadata(&env, &cfg.tea_nft, token_b_id);
+        genealogy::ensure_recorded(&env, offer.token_a_id, &parent_a);
+        track_freshness(&env, &cfg, offer.token_a_id);
+        genealogy::ensure_recorded(&env, token_b_id, &parent_b);
+        track_freshness(&env, &cfg, token_b_id);
+        let recipe = discover_hidden(
             &env,
-            &cfg.tea_nft,
-            &env.current_contract_address(),
-            offer.token_a_id,
-        );
-        util::burn_tea(
+            &cfg,
+            &vec![&env, parent_a.clone(), parent_b.clone()],
+            &winner,
+        )
+        .unwrap_or(recipe);
+
+        if rules.consume_parents {
+            util::burn_tea(&env, &cfg.tea_nft, &contract_address, offer.token_a_id);
+            util::burn_tea(&env, &cfg.tea_nft, &contract_address, token_b_id);
+            profiles::record_burned(&env, &owner, 1);
+            profiles::record_burned(&env, &partner, 1);
+        } else {
+            util::transfer_tea(
+                &env,
+                &cfg.tea_nft,
+                &contract_address,
+                &owner,
+                offer.token_a_id,
+            );
+            util::transfer_tea(&env, &cfg.tea_nft, &contract_address, &partner, token_b_id);
+        }
+
+        let metadata = compose_metadata(
             &env,
-            &cfg.tea_nft,
-            &env.current_contract_address(),
-            token_b_id,
+            &recipe,
+            offer_parents(&env, &offer),
+            offer.desired_profile.clone(),
         );
+        let parents = offer_parents(&env, &offer);
+        let parents_survive = !rules.consume_parents;
+        let (new_token_id, partner_token_id) = if cooperative {
+            let mut owner_child = metadata.clone();
+            owner_child.stats = vary_stats(&metadata.stats, &seed[0..3]);
+            let mut partner_child = metadata;
+            partner_child.stats = vary_stats(&partner_child.stats, &seed[3..6]);
+            let owner_token = mint_tea(&env, &cfg, &winner, owner_child.clone());
+            let partner_token = mint_tea(&env, &cfg, &loser, partner_child.clone());
+            record_offspring(
+                &env,
+                recipe.id,
+                &parents,
+                parents_survive,
+                &[(owner_token, &owner_child), (partner_token, &partner_child)],
+            );
+            (owner_token, Some(partner_token))
+        } else {
+            let token = mint_tea(&env, &cfg, &winner, metadata.clone());
+            record_offspring(
+                &env,
+                recipe.id,
+                &parents,
+                parents_survive,
+                &[(token, &metadata)],
+            );
+            (token, None)
+        };
 
-        let metadata = compose_metadata(&env, &recipe, &offer);
-        let new_token_id = util::mint_tea(&env, &cfg.tea_nft, &winner, metadata);
-
-        let total_balls = offer.fee_balls + offer.partner_fee_balls;
-        let total_stars = offer.fee_stars + offer.partner_fee_stars;
-        let contract_address = env.current_contract_address();
+        let mut total_balls = offer.fee_balls + offer.partner_fee_balls;
+        let mut total_stars = offer.fee_stars + offer.partner_fee_stars;
+        let mut keeper_balls = 0;
+        let mut keeper_stars = 0;
 
-        if total_balls > 0 {
-            let (loser_share_balls, treasury_share_balls) = StellarTeaGame::split_fee(total_balls);
-            if loser_share_balls > 0 {
+        if let Some(keeper) = keeper {
+            keeper_balls = total_balls * KEEPER_REWARD_BPS / 10_000;
+            keeper_stars = total_stars * KEEPER_REWARD_BPS / 10_000;
+            if keeper_balls > 0 {
                 util::transfer(
                     &env,
                     &cfg.balls_token,
                     &contract_address,
-                    &loser,
-                    loser_share_balls,
+                    &keeper,
+                    keeper_balls,
                 );
             }
-            if treasury_share_balls > 0 {
+            if keeper_stars > 0 {
                 util::transfer(
                     &env,
-                    &cfg.balls_token,
+                    &cfg.stars_token,
                     &contract_address,
-                    &cfg.treasury,
-                    treasury_share_balls,
+                    &keeper,
+                    keeper_stars,
                 );
             }
+            total_balls -= keeper_balls;
+            total_stars -= keeper_stars;
+            env.events().publish(
+                ("keeper_rewarded",),
+                (offer_id, keeper, keeper_balls, keeper_stars),
+            );
         }
 
-        if total_stars > 0 {
-            let (loser_share_stars, treasury_share_stars) = StellarTeaGame::split_fee(total_stars);
-            if loser_share_stars > 0 {
-                util::transfer(
-                    &env,
-                    &cfg.stars_token,
-                    &contract_address,
-                    &loser,
-                    loser_share_stars,
-                );
-            }
-            if treasury_share_stars > 0 {
-                util::transfer(
-                    &env,
-                    &cfg.stars_token,
-                    &contract_address,
-                    &cfg.treasury,
-                    treasury_share_stars,
-                );
-            }
+        let (loser_share_balls, treasury_share_balls) =
+            StellarTeaGame::mix_fee_split(total_balls, &rules.mode);
+        if loser_share_balls > 0 {
+            util::transfer(
+                &env,
+                &cfg.balls_token,
+                &contract_address,
+                &loser,
+                loser_share_balls,
+            );
+        }
+        if treasury_share_balls > 0 {
+            util::transfer(
+                &env,
+                &cfg.balls_token,
+                &contract_address,
+                &cfg.treasury,
+                treasury_share_balls,
+            );
+        }
+
+        let (loser_share_stars, treasury_share_stars) =
+            StellarTeaGame::mix_fee_split(total_stars, &rules.mode);
+        if loser_share_stars > 0 {
+            util::transfer(
+                &env,
+                &cfg.stars_token,
+                &contract_address,
+                &loser,
+                loser_share_stars,
+            );
+        }
+        if treasury_share_stars > 0 {
+            util::transfer(
+                &env,
+                &cfg.stars_token,
+                &contract_address,
+                &cfg.treasury,
+                treasury_share_stars,
+            );
+        }
+
+        let receipt = MixReceipt {
+            owner: owner.clone(),
+            partner,
+            token_a_id: offer.token_a_id,
+            token_b_id,
+            parent_a,
+            parent_b,
+            offered_recipe_id: offer.recipe_id,
+            produced_recipe_id: recipe.id,
+            mode: rules.mode.clone(),
+            seed_timestamp: env.ledger().timestamp(),
+            seed: BytesN::from_array(&env, &seed),
+            winner: winner.clone(),
+            loser: loser.clone(),
+            child_token_id: new_token_id,
+            partner_child_token_id: partner_token_id,
+            fees: FeeDistribution {
+                keeper_balls,
+                keeper_stars,
+                loser_balls: loser_share_balls,
+                loser_stars: loser_share_stars,
+                treasury_balls: treasury_share_balls,
+                treasury_stars: treasury_share_stars,
+            },
+        };
+        mixing::put_receipt(&env, offer_id, &receipt);
+        record_activity(
+            &env,
+            &cfg,
+            &owner,
+            HistoryKind::Mix,
+            Some(new_token_id),
+            offer.fee_balls,
+            offer.fee_stars,
+        );
+        record_activity(
+            &env,
+            &cfg,
+            &receipt.partner,
+            HistoryKind::Mix,
+            Some(partner_token_id.unwrap_or(new_token_id)),
+            offer.partner_fee_balls,
+            offer.partner_fee_stars,
+        );
+        track_progress(&env, &cfg, &owner, QuestAction::Mix(recipe.id));
+        track_progress(&env, &cfg, &receipt.partner, QuestAction::Mix(recipe.id));
+        if !cooperative {
+            profiles::record_win(&env, &winner);
         }
 
         mixing::remove(&env, offer_id);
-        mixing::clear_owner_index(&env, &owner, offer.recipe_id);
+        mixing::remove_owner_offer(&env, &owner, offer_id);
         offer.status = OfferStatus::Completed;
 
         let outcome = MixOutcome {
             new_token_id,
+            partner_token_id,
             winner: winner.clone(),
             loser: loser.clone(),
             total_balls,
             total_stars,
         };
 
-        env.events().publish(
-            ("mix_resolved",),
-            (
-                offer_id,
-                outcome.winner.clone(),
-                outcome.loser.clone(),
-                outcome.new_token_id,
-                outcome.total_balls,
-                outcome.total_stars,
-            ),
-        );
+        if let Some(partner_token_id) = outcome.partner_token_id {
+            env.events().publish(
+                ("mix_cooperative",),
+                (
+                    offer_id,
+                    outcome.winner.clone(),
+                    outcome.loser.clone(),
+                    outcome.new_token_id,
+                    partner_token_id,
+                ),
+            );
+        } else {
+            env.events().publish(
+                ("mix_resolved",),
+                (
+                    offer_id,
+                    outcome.winner.clone(),
+                    outcome.loser.clone(),
+                    outcome.new_token_id,
+                    outcome.total_balls,
+                    outcome.total_stars,
+                ),
+            );
+        }
 
         Ok(outcome)
     }
 
-    fn split_fee(total: i128) -> (i128, i128) {
-        if total <= 0 {
-            return (0, 0);
-        }
-        let loser_share = total * LOSER_COMPENSATION_PERCENT / 100;
-        let mut treasury_share = total * TREASURY_REWARD_PERCENT / 100;
-        let distributed = loser_share + treasury_share;
-        if distributed < total {
-            treasury_share += total - distributed;
+    fn mix_fee_split(total: i128, mode: &MixMode) -> (i128, i128) {
+        match mode {
+            MixMode::Competitive => StellarTeaGame::split_fee(total),
+            MixMode::Cooperative => (0, total.max(0)),
         }
-        (loser_share, treasury_share)
     }
 
-    pub fn __constructor(
+    fn resolve_session(
         env: Env,
-        admin: Address,
-        treasury: Address,
-        balls_token: Address,
-        stars_token: Address,
-        tea_nft: Address,
-        dex: Option<Address>,
-    ) {
-        config::init(
+        session_id: u64,
+        mut session: MixSession,
+    ) -> Result<u64, GameError> {
+        let cfg = config::get(&env);
+        let recipe = get_recipe(&env, session.recipe_id)?;
+        let contract_address = env.current_contract_address();
+        let winner_index = session_winner(&env, session_id, &session);
+        let winner = session
+            .contributions
+            .get(winner_index)
+            .ok_or(GameError::NotReady)?
+            .contributor;
+
+        let mut lineage = Vec::new(&env);
+        let mut total_balls = 0i128;
+        let mut total_stars = 0i128;
+        for contribution in session.contributions.iter() {
+            util::burn_tea(&env, &cfg.tea_nft, &contract_address, contribution.token_id);
+            profiles::record_burned(&env, &contribution.contributor, 1);
+            lineage.push_back(contribution.token_id);
+            total_balls += contribution.fee_balls;
+            total_stars += contribution.fee_stars;
+        }
+
+        let metadata = compose_metadata(
             &env,
-            &admin,
-            &treasury,
-            &balls_token,
+            &recipe,
+            lineage.clone(),
+            recipe.flavor_profile.clone(),
+        );
+        let new_token_id = mint_tea(&env, &cfg, &winner, metadata.clone());
+        record_offspring(
+            &env,
+            recipe.id,
+            &lineage,
+            false,
+            &[(new_token_id, &metadata)],
+        );
+
+        // The loser share is spread evenly over every contribution except the winning one.
+        // The BALLS kept back are what the contributors paid into the child.
+        let losing_contributions = session.contributions.len() as i128 - 1;
+        let mut retained_balls = 0;
+        for (token, total) in [
+            (&cfg.balls_token, total_balls),
+            (&cfg.stars_token, total_stars),
+        ] {
+            if total <= 0 {
+                continue;
+            }
+            let (loser_share, treasury_share) = StellarTeaGame::split_fee(total);
+            let per_loser = loser_share / losing_contributions;
+            for (index, contribution) in session.contributions.iter().enumerate() {
+                if index as u32 != winner_index && per_loser > 0 {
+                    util::transfer(
+                        &env,
+                        token,
+                        &contract_address,
+                        &contribution.contributor,
+                        per_loser,
+                    );
+                }
+            }
+            let to_treasury = treasury_share + loser_share - per_loser * losing_contributions;
+            if to_treasury > 0 {
+                util::transfer(&env, token, &contract_address, &cfg.treasury, to_treasury);
+            }
+            if *token == cfg.balls_token {
+                retained_balls = to_treasury;
+            }
+        }
+        salvage::record_investment(&env, new_token_id, retained_balls);
+
+        for contribution in session.contributions.iter() {
+            record_activity(
+                &env,
+                &cfg,
+                &contribution.contributor,
+                HistoryKind::Mix,
+                Some(new_token_id),
+                contribution.fee_balls,
+                contribution.fee_stars,
+            );
+            track_progress(
+                &env,
+                &cfg,
+                &contribution.contributor,
+                QuestAction::Mix(recipe.id),
+            );
+        }
+        profiles::record_win(&env, &winner);
+        session.status = SessionStatus::Completed;
+        sessions::put(&env, session_id, &session);
+        env.events().publish(
+            ("session_resolved",),
+            (session_id, winner, new_token_id, total_balls, total_stars),
+        );
+        Ok(new_token_id)
+    }
+
+    fn split_fee(total: i128) -> (i128, i128) {
+        if total <= 0 {
+            return (0, 0);
+        }
+        let loser_share = total * LOSER_COMPENSATION_PERCENT / 100;
+        let mut treasury_share = total * TREASURY_REWARD_PERCENT / 100;
+        let distributed = loser_share + treasury_share;
+        if distributed < total {
+            treasury_share += total - distributed;
+        }
+        (loser_share, treasury_share)
+    }
+
+    pub fn __constructor(
+        env: Env,
+        admin: Address,
+        treasury: Address,
+        balls_token: Address,
+        stars_token: Address,
+        tea_nft: Address,
+        dex: Option<Address>,
+    ) {
+        config::init(
+            &env,
+            &admin,
+            &treasury,
+            &balls_token,
             &stars_token,
             &tea_nft,
             &dex,
@@ -297,6 +1095,124 @@ impl StellarTeaGame {
         Ok(())
     }
 
+    pub fn set_recipe_mix_rules(
+        env: Env,
+        recipe_id: u32,
+        mode: MixMode,
+        consume_parents: bool,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        let rules = MixRules {
+            mode: mode.clone(),
+            consume_parents,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::MixRules(recipe_id), &rules);
+        env.events()
+            .publish(("recipe_rules_set",), (recipe_id, mode, consume_parents));
+        Ok(())
+    }
+
+    /// Mix rules of a recipe; `None` while the recipe is hidden and undiscovered.
+    pub fn recipe_mix_rules(env: Env, recipe_id: u32) -> Option<MixRules> {
+        if is_locked(&env, recipe_id) {
+            return None;
+        }
+        Some(get_mix_rules(&env, recipe_id))
+    }
+
+    /// Sets or clears a recipe's parent requirements. A hidden recipe is only discovered
+    /// through its requirements, so they cannot be cleared while it is still locked.
+    pub fn set_recipe_requirements(
+        env: Env,
+        recipe_id: u32,
+        requirements: Option<RecipeRequirements>,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        if requirements.is_none() && is_locked(&env, recipe_id) {
+            return Err(GameError::InvalidInput);
+        }
+        let key = DataKey::Requirements(recipe_id);
+        match requirements {
+            Some(requirements) => env.storage().instance().set(&key, &requirements),
+            None => env.storage().instance().remove(&key),
+        }
+        env.events()
+            .publish(("recipe_requirements_set",), (recipe_id,));
+        Ok(())
+    }
+
+    /// Parent requirements of a recipe; `None` while the recipe is hidden and undiscovered.
+    pub fn recipe_requirements(env: Env, recipe_id: u32) -> Option<RecipeRequirements> {
+        if is_locked(&env, recipe_id) {
+            return None;
+        }
+        get_requirements(&env, recipe_id)
+    }
+
+    /// Hides a recipe until a mix first satisfies its requirements.
+    pub fn set_recipe_hidden(env: Env, recipe_id: u32, hidden: bool) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        if hidden && get_requirements(&env, recipe_id).is_none() {
+            return Err(GameError::InvalidInput);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Hidden(recipe_id), &hidden);
+        set_hidden_listed(&env, recipe_id, is_locked(&env, recipe_id));
+        env.events()
+            .publish(("recipe_hidden_set",), (recipe_id, hidden));
+        Ok(())
+    }
+
+    pub fn set_discovery_reward(env: Env, balls: i128, stars: i128) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if balls < 0 || stars < 0 {
+            return Err(GameError::InvalidInput);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::DiscoveryReward, &(balls, stars));
+        Ok(())
+    }
+
+    pub fn recipe_discovery(env: Env, recipe_id: u32) -> Option<Discovery> {
+        get_discovery(&env, recipe_id)
+    }
+
+    pub fn list_recipes(env: Env) -> Vec<Recipe> {
+        let mut recipes = Vec::new(&env);
+        for recipe_id in recipe_ids(&env).iter() {
+            if let Ok(recipe) = get_playable_recipe(&env, recipe_id) {
+                recipes.push_back(recipe);
+            }
+        }
+        recipes
+    }
+
+    /// Recipes a mix of the two given teas can produce. Recipes without requirements accept
+    /// any pair of parents; recipes with ingredient slots only resolve through sessions.
+    pub fn resolve_recipe(env: Env, token_a: u64, token_b: u64) -> Vec<u32> {
+        let cfg = config::get(&env);
+        let parent_a = util::get_tea_metadata(&env, &cfg.tea_nft, token_a);
+        let parent_b = util::get_tea_metadata(&env, &cfg.tea_nft, token_b);
+        let mut matches = Vec::new(&env);
+        for recipe_id in recipe_ids(&env).iter() {
+            if is_locked(&env, recipe_id) || !sessions::slots(&env, recipe_id).is_empty() {
+                continue;
+            }
+            let parents = vec![&env, parent_a.clone(), parent_b.clone()];
+            if ensure_recipe_accepts(&env, recipe_id, &parents).is_ok() {
+                matches.push_back(recipe_id);
+            }
+        }
+        matches
+    }
+
     pub fn set_daily_limit(
         env: Env,
         user: Address,
@@ -339,6 +1255,7 @@ impl StellarTeaGame {
         fee_balls: i128,
         fee_stars: i128,
         deadline: u64,
+        mode: OfferMode,
     ) -> Result<u64, GameError> {
         ensure_authorized_player(&env, &owner)?;
         ensure_fee_schedule(fee_balls, fee_stars)?;
@@ -348,9 +1265,12 @@ impl StellarTeaGame {
         if deadline <= now {
             return Err(GameError::InvalidInput);
         }
-        let _ = get_recipe(&env, recipe_id)?;
-        if mixing::get_by_owner_recipe(&env, &owner, recipe_id).is_some() {
-            return Err(GameError::InvalidInput);
+        let _ = get_playable_recipe(&env, recipe_id)?;
+        badges::ensure_holds(&env, &owner, badges::recipe_gate(&env, recipe_id))?;
+        ensure_playable_tea(&env, &cfg, token_a_id)?;
+        breeding::ensure_can_breed(&env, token_a_id, recipe_id)?;
+        if mixing::owner_offers(&env, &owner).len() >= config::max_offers_per_owner(&env) {
+            return Err(GameError::LimitExceeded);
         }
 
         // escrow NFT and tokens
@@ -395,16 +1315,21 @@ impl StellarTeaGame {
             partner_fee_balls: 0,
             partner_fee_stars: 0,
             status: OfferStatus::WaitingForPartner,
+            mode,
+            accepted_at: None,
             created_at: now,
             deadline,
         };
         mixing::put(&env, offer_id, &offer);
-        mixing::set_owner_index(&env, &owner, recipe_id, offer_id);
+        mixing::add_owner_offer(&env, &owner, offer_id);
         env.events()
             .publish(("mix_offer_created",), (owner, offer_id, recipe_id));
         Ok(offer_id)
     }
 
+    /// Accepts an offer at the owner's fee. Instant offers resolve at once and return the
+    /// child id (the owner's child in cooperative mixes); deferred offers return `None`
+    /// until finalised.
     pub fn accept_mix_offer(
         env: Env,
         offer_id: u64,
@@ -412,7 +1337,7 @@ impl StellarTeaGame {
         token_b_id: u64,
         fee_balls: i128,
         fee_stars: i128,
-    ) -> Result<u64, GameError> {
+    ) -> Result<Option<u64>, GameError> {
         ensure_authorized_player(&env, &partner)?;
         let cfg = config::get(&env);
         let mut offer = mixing::get(&env, offer_id)?;
@@ -422,12 +1347,31 @@ impl StellarTeaGame {
         if env.ledger().timestamp() > offer.deadline {
             return Err(GameError::Expired);
         }
+        if offer.mode == OfferMode::Bidding {
+            return Err(GameError::InvalidInput);
+        }
+        badges::ensure_holds(&env, &partner, badges::recipe_gate(&env, offer.recipe_id))?;
+        ensure_playable_tea(&env, &cfg, token_b_id)?;
         if fee_balls != offer.fee_balls {
             return Err(GameError::InvalidInput);
         }
         if fee_stars != offer.fee_stars {
             return Err(GameError::InvalidInput);
         }
+        breeding::ensure_can_breed(&env, token_b_id, offer.recipe_id)?;
+        let partner_tea = util::get_tea_metadata(&env, &cfg.tea_nft, token_b_id);
+        if !offer_accepts(&offer, &partner_tea) {
+            return Err(GameError::InvalidInput);
+        }
+        ensure_recipe_accepts(
+            &env,
+            offer.recipe_id,
+            &vec![
+                &env,
+                util::get_tea_metadata(&env, &cfg.tea_nft, offer.token_a_id),
+                partner_tea,
+            ],
+        )?;
 
         util::transfer_tea(
             &env,
@@ -461,7 +1405,14 @@ impl StellarTeaGame {
         offer.partner_fee_balls = fee_balls;
         offer.partner_fee_stars = fee_stars;
         offer.status = OfferStatus::ReadyToMix;
-        let outcome = StellarTeaGame::resolve_mix(env.clone(), offer_id, offer)?;
+        if offer.mode == OfferMode::Deferred {
+            offer.accepted_at = Some(env.ledger().timestamp());
+            mixing::put(&env, offer_id, &offer);
+            env.events()
+                .publish(("mix_offer_accepted",), (offer_id, partner, token_b_id));
+            return Ok(None);
+        }
+        let outcome = StellarTeaGame::resolve_mix(env.clone(), offer_id, offer, None)?;
         env.events().publish(
             ("mix_offer_completed",),
             (
@@ -471,138 +1422,978 @@ impl StellarTeaGame {
                 outcome.new_token_id,
             ),
         );
-        Ok(outcome.new_token_id)
+        Ok(Some(outcome.new_token_id))
     }
 
-    pub fn cancel_mix_offer(env: Env, owner: Address, recipe_id: u32) -> Result<(), GameError> {
-        ensure_authorized_player(&env, &owner)?;
+    /// Lets the partner of a deferred offer take back their tea and fees while the
+    /// withdrawal window is open. The offer returns to `WaitingForPartner`.
+    pub fn withdraw_from_mix(env: Env, offer_id: u64, partner: Address) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &partner)?;
         let cfg = config::get(&env);
-        let offer_id =
-            mixing::get_by_owner_recipe(&env, &owner, recipe_id).ok_or(GameError::OfferNotFound)?;
-        let offer = mixing::get(&env, offer_id)?;
-        if offer.status != OfferStatus::WaitingForPartner {
-            return Err(GameError::OfferClosed);
+        let mut offer = mixing::get(&env, offer_id)?;
+        if offer.status != OfferStatus::ReadyToMix || offer.mode != OfferMode::Deferred {
+            return Err(GameError::NotReady);
         }
+        if offer.owner_b.as_ref() != Some(&partner) {
+            return Err(GameError::Unauthorized);
+        }
+        let accepted_at = offer.accepted_at.ok_or(GameError::NotReady)?;
+        if env.ledger().timestamp() > accepted_at + DEFERRED_WITHDRAWAL_WINDOW {
+            return Err(GameError::Expired);
+        }
+        let token_b_id = offer.token_b_id.ok_or(GameError::NotReady)?;
 
-        util::transfer_tea(
-            &env,
-            &cfg.tea_nft,
-            &env.current_contract_address(),
-            &owner,
-            offer.token_a_id,
-        );
-
-        if offer.fee_balls > 0 {
+        let contract_address = env.current_contract_address();
+        util::transfer_tea(&env, &cfg.tea_nft, &contract_address, &partner, token_b_id);
+        if offer.partner_fee_balls > 0 {
             util::transfer(
                 &env,
                 &cfg.balls_token,
-                &env.current_contract_address(),
-                &owner,
-                offer.fee_balls,
+                &contract_address,
+                &partner,
+                offer.partner_fee_balls,
             );
         }
-        if offer.fee_stars > 0 {
+        if offer.partner_fee_stars > 0 {
             util::transfer(
                 &env,
                 &cfg.stars_token,
-                &env.current_contract_address(),
-                &owner,
-                offer.fee_stars,
+                &contract_address,
+                &partner,
+                offer.partner_fee_stars,
             );
         }
 
-        mixing::remove(&env, offer_id);
-        mixing::clear_owner_index(&env, &owner, recipe_id);
+        offer.owner_b = None;
+        offer.token_b_id = None;
+        offer.partner_fee_balls = 0;
+        offer.partner_fee_stars = 0;
+        offer.accepted_at = None;
+        offer.status = OfferStatus::WaitingForPartner;
+        mixing::put(&env, offer_id, &offer);
         env.events()
-            .publish(("mix_offer_cancelled",), (owner, recipe_id));
+            .publish(("mix_partner_withdrawn",), (offer_id, partner));
+        Ok(())
+    }
+
+    pub fn set_max_offers_per_owner(env: Env, cap: u32) -> Result<(), GameError> {
+        if cap == 0 {
+            return Err(GameError::InvalidInput);
+        }
+        config::set_max_offers_per_owner(&env, cap);
+        env.events().publish(("offer_cap_set",), (cap,));
         Ok(())
     }
 
-    pub fn mix_tea(
+    pub fn owner_offers(env: Env, owner: Address) -> Vec<u64> {
+        mixing::owner_offers(&env, &owner)
+    }
+
+    /// Pairs two open offers for the same recipe whose criteria satisfy each other and
+    /// resolves them at once. The keeper, who may not own either offer, receives a small
+    /// cut of the pooled fees.
+    pub fn match_offers(
         env: Env,
-        owner: Address,
-        recipe_id: u32,
-        balls: i128,
-        stars: Option<i128>,
+        keeper: Address,
+        offer_a: u64,
+        offer_b: u64,
     ) -> Result<u64, GameError> {
-        let offer_id =
-            mixing::get_by_owner_recipe(&env, &owner, recipe_id).ok_or(GameError::OfferNotFound)?;
-        let offer = mixing::get(&env, offer_id)?;
-        if offer.status != OfferStatus::ReadyToMix {
-            return Err(GameError::NotReady);
-        }
-        if offer.recipe_id != recipe_id {
+        ensure_authorized_player(&env, &keeper)?;
+        if offer_a == offer_b {
             return Err(GameError::InvalidInput);
         }
-        if offer.owner_a != owner {
+        let cfg = config::get(&env);
+        let mut offer = mixing::get(&env, offer_a)?;
+        let counter = mixing::get(&env, offer_b)?;
+        let now = env.ledger().timestamp();
+        for candidate in [&offer, &counter] {
+            if candidate.status != OfferStatus::WaitingForPartner {
+                return Err(GameError::OfferClosed);
+            }
+            if now > candidate.deadline {
+                return Err(GameError::Expired);
+            }
+        }
+        if keeper == offer.owner_a || keeper == counter.owner_a {
             return Err(GameError::Unauthorized);
         }
-        let expected_balls = offer.fee_balls + offer.partner_fee_balls;
-        if expected_balls != balls {
+        if offer.recipe_id != counter.recipe_id
+            || offer.mode != OfferMode::Instant
+            || counter.mode != OfferMode::Instant
+            || offer.owner_a == counter.owner_a
+            || offer.fee_balls != counter.fee_balls
+            || offer.fee_stars != counter.fee_stars
+        {
             return Err(GameError::InvalidInput);
         }
-        let expected_stars = offer.fee_stars + offer.partner_fee_stars;
-        if expected_stars != stars.unwrap_or(0) {
+
+        let tea_a = util::get_tea_metadata(&env, &cfg.tea_nft, offer.token_a_id);
+        let tea_b = util::get_tea_metadata(&env, &cfg.tea_nft, counter.token_a_id);
+        if !offer_accepts(&offer, &tea_b) || !offer_accepts(&counter, &tea_a) {
             return Err(GameError::InvalidInput);
         }
+        ensure_recipe_accepts(&env, offer.recipe_id, &vec![&env, tea_a, tea_b])?;
+        breeding::ensure_can_breed(&env, offer.token_a_id, offer.recipe_id)?;
+        breeding::ensure_can_breed(&env, counter.token_a_id, offer.recipe_id)?;
+
+        // Both teas and fees are already escrowed; fold the counter offer into the first one.
+        mixing::remove(&env, offer_b);
+        mixing::remove_owner_offer(&env, &counter.owner_a, offer_b);
+        offer.owner_b = Some(counter.owner_a.clone());
+        offer.token_b_id = Some(counter.token_a_id);
+        offer.partner_fee_balls = counter.fee_balls;
+        offer.partner_fee_stars = counter.fee_stars;
+        offer.status = OfferStatus::ReadyToMix;
 
-        let outcome = StellarTeaGame::resolve_mix(env.clone(), offer_id, offer)?;
+        let outcome =
+            StellarTeaGame::resolve_mix(env.clone(), offer_a, offer, Some(keeper.clone()))?;
+        // The counterparty audits the mix through its own offer id.
+        let receipt = mixing::get_receipt(&env, offer_a)?;
+        mixing::put_receipt(&env, offer_b, &receipt);
         env.events().publish(
-            ("mix_offer_completed",),
-            (
-                offer_id,
-                outcome.winner.clone(),
-                outcome.loser.clone(),
-                outcome.new_token_id,
-            ),
+            ("mix_offers_matched",),
+            (offer_a, offer_b, keeper, outcome.new_token_id),
         );
         Ok(outcome.new_token_id)
     }
 
-    pub fn upgrade_tea(
+    /// Escrows a partner tea and a self-chosen fee against a bidding offer.
+    pub fn place_mix_bid(
         env: Env,
-        owner: Address,
-        nft_id: u64,
-        balls: i128,
-        stars: i128,
+        offer_id: u64,
+        bidder: Address,
+        token_id: u64,
+        fee_balls: i128,
+        fee_stars: i128,
     ) -> Result<(), GameError> {
-        ensure_authorized_player(&env, &owner)?;
-        assert_payment(balls)?;
-        assert_payment(stars)?;
+        ensure_authorized_player(&env, &bidder)?;
+        ensure_fee_schedule(fee_balls, fee_stars)?;
         let cfg = config::get(&env);
-        let token_owner = util::owner_of(&env, &cfg.tea_nft, nft_id);
-        if token_owner != owner {
-            return Err(GameError::NotOwner);
+        let offer = mixing::get(&env, offer_id)?;
+        if offer.status != OfferStatus::WaitingForPartner {
+            return Err(GameError::OfferClosed);
         }
-
-        util::transfer_from(
-            &env,
-            &cfg.balls_token,
-            &owner,
-            &env.current_contract_address(),
-            balls,
-        );
-        util::transfer_from(
+        if env.ledger().timestamp() > offer.deadline {
+            return Err(GameError::Expired);
+        }
+        if offer.mode != OfferMode::Bidding || offer.owner_a == bidder {
+            return Err(GameError::InvalidInput);
+        }
+        badges::ensure_holds(&env, &bidder, badges::recipe_gate(&env, offer.recipe_id))?;
+        ensure_playable_tea(&env, &cfg, token_id)?;
+        let mut bids = mixing::bids(&env, offer_id);
+        if bids.len() >= mixing::MAX_BIDS_PER_OFFER {
+            return Err(GameError::LimitExceeded);
+        }
+        if bids.iter().any(|bid| bid.bidder == bidder) {
+            return Err(GameError::InvalidInput);
+        }
+        breeding::ensure_can_breed(&env, token_id, offer.recipe_id)?;
+        let bid_tea = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+        if !offer_accepts(&offer, &bid_tea) {
+            return Err(GameError::InvalidInput);
+        }
+        ensure_recipe_accepts(
             &env,
-            &cfg.stars_token,
-            &owner,
-            &env.current_contract_address(),
-            stars,
-        );
+            offer.recipe_id,
+            &vec![
+                &env,
+                util::get_tea_metadata(&env, &cfg.tea_nft, offer.token_a_id),
+                bid_tea,
+            ],
+        )?;
 
-        let burn_balls = balls / 2;
-        let burn_stars = stars / 2;
-        util::burn(
-            &env,
-            &cfg.balls_token,
-            &env.current_contract_address(),
-            burn_balls,
-        );
-        util::burn(
-            &env,
-            &cfg.stars_token,
-            &env.current_contract_address(),
-            burn_stars,
+        let contract_address = env.current_contract_address();
+        util::transfer_tea(&env, &cfg.tea_nft, &bidder, &contract_address, token_id);
+        if fee_balls > 0 {
+            util::transfer_from(
+                &env,
+                &cfg.balls_token,
+                &bidder,
+                &contract_address,
+                fee_balls,
+            );
+        }
+        if fee_stars > 0 {
+            util::transfer_from(
+                &env,
+                &cfg.stars_token,
+                &bidder,
+                &contract_address,
+                fee_stars,
+            );
+        }
+
+        bids.push_back(MixBid {
+            bidder: bidder.clone(),
+            token_id,
+            fee_balls,
+            fee_stars,
+            created_at: env.ledger().timestamp(),
+        });
+        mixing::set_bids(&env, offer_id, &bids);
+        env.events().publish(
+            ("mix_bid_placed",),
+            (offer_id, bidder, token_id, fee_balls, fee_stars),
+        );
+        Ok(())
+    }
+
+    pub fn withdraw_mix_bid(env: Env, offer_id: u64, bidder: Address) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &bidder)?;
+        let cfg = config::get(&env);
+        let mut bids = mixing::bids(&env, offer_id);
+        let index = bids
+            .iter()
+            .position(|bid| bid.bidder == bidder)
+            .ok_or(GameError::OfferNotFound)? as u32;
+        let bid = bids.get(index).ok_or(GameError::OfferNotFound)?;
+        refund_bid(&env, &cfg, offer_id, &bid);
+        bids.remove(index);
+        mixing::set_bids(&env, offer_id, &bids);
+        Ok(())
+    }
+
+    /// Accepts one bid before the deadline, refunds every other bid and resolves the mix.
+    pub fn select_mix_bid(
+        env: Env,
+        owner: Address,
+        offer_id: u64,
+        bidder: Address,
+    ) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        let cfg = config::get(&env);
+        let mut offer = mixing::get(&env, offer_id)?;
+        if offer.owner_a != owner {
+            return Err(GameError::Unauthorized);
+        }
+        if offer.status != OfferStatus::WaitingForPartner {
+            return Err(GameError::OfferClosed);
+        }
+        if env.ledger().timestamp() > offer.deadline {
+            return Err(GameError::Expired);
+        }
+        if offer.mode != OfferMode::Bidding {
+            return Err(GameError::InvalidInput);
+        }
+
+        let mut selected = None;
+        for bid in mixing::bids(&env, offer_id).iter() {
+            if bid.bidder == bidder {
+                selected = Some(bid);
+            } else {
+                refund_bid(&env, &cfg, offer_id, &bid);
+            }
+        }
+        let bid = selected.ok_or(GameError::OfferNotFound)?;
+        mixing::set_bids(&env, offer_id, &Vec::new(&env));
+
+        offer.owner_b = Some(bid.bidder.clone());
+        offer.token_b_id = Some(bid.token_id);
+        offer.partner_fee_balls = bid.fee_balls;
+        offer.partner_fee_stars = bid.fee_stars;
+        offer.status = OfferStatus::ReadyToMix;
+        let outcome = StellarTeaGame::resolve_mix(env.clone(), offer_id, offer, None)?;
+        env.events().publish(
+            ("mix_bid_selected",),
+            (
+                offer_id,
+                bidder,
+                bid.fee_balls,
+                bid.fee_stars,
+                outcome.new_token_id,
+            ),
+        );
+        Ok(outcome.new_token_id)
+    }
+
+    pub fn get_mix_receipt(env: Env, offer_id: u64) -> Result<MixReceipt, GameError> {
+        mixing::get_receipt(&env, offer_id)
+    }
+
+    pub fn mix_bids(env: Env, offer_id: u64) -> Vec<MixBid> {
+        mixing::bids(&env, offer_id)
+    }
+
+    pub fn cancel_mix_offer(env: Env, owner: Address, offer_id: u64) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        let cfg = config::get(&env);
+        let offer = mixing::get(&env, offer_id)?;
+        if offer.owner_a != owner {
+            return Err(GameError::Unauthorized);
+        }
+        if offer.status != OfferStatus::WaitingForPartner {
+            return Err(GameError::OfferClosed);
+        }
+
+        util::transfer_tea(
+            &env,
+            &cfg.tea_nft,
+            &env.current_contract_address(),
+            &owner,
+            offer.token_a_id,
+        );
+
+        if offer.fee_balls > 0 {
+            util::transfer(
+                &env,
+                &cfg.balls_token,
+                &env.current_contract_address(),
+                &owner,
+                offer.fee_balls,
+            );
+        }
+        if offer.fee_stars > 0 {
+            util::transfer(
+                &env,
+                &cfg.stars_token,
+                &env.current_contract_address(),
+                &owner,
+                offer.fee_stars,
+            );
+        }
+
+        for bid in mixing::bids(&env, offer_id).iter() {
+            refund_bid(&env, &cfg, offer_id, &bid);
+        }
+        mixing::set_bids(&env, offer_id, &Vec::new(&env));
+
+        mixing::remove(&env, offer_id);
+        mixing::remove_owner_offer(&env, &owner, offer_id);
+        env.events()
+            .publish(("mix_offer_cancelled",), (owner, offer_id, offer.recipe_id));
+        Ok(())
+    }
+
+    /// Finalises a deferred offer once the partner withdrawal window has passed. The owner
+    /// or any keeper may finalise; keepers earn the keeper cut. The partner may force
+    /// resolution after the timeout.
+    pub fn mix_tea(env: Env, caller: Address, offer_id: u64) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &caller)?;
+        let offer = mixing::get(&env, offer_id)?;
+        if offer.status != OfferStatus::ReadyToMix || offer.mode != OfferMode::Deferred {
+            return Err(GameError::NotReady);
+        }
+        let accepted_at = offer.accepted_at.ok_or(GameError::NotReady)?;
+        let elapsed = env.ledger().timestamp().saturating_sub(accepted_at);
+        if elapsed <= DEFERRED_WITHDRAWAL_WINDOW {
+            return Err(GameError::NotReady);
+        }
+
+        let keeper = if caller == offer.owner_a {
+            None
+        } else if offer.owner_b.as_ref() == Some(&caller) {
+            if elapsed <= DEFERRED_FORCE_TIMEOUT {
+                return Err(GameError::NotReady);
+            }
+            None
+        } else {
+            Some(caller.clone())
+        };
+
+        let outcome = StellarTeaGame::resolve_mix(env.clone(), offer_id, offer, keeper)?;
+        env.events().publish(
+            ("mix_offer_completed",),
+            (
+                offer_id,
+                outcome.winner.clone(),
+                outcome.loser.clone(),
+                outcome.new_token_id,
+            ),
+        );
+        Ok(outcome.new_token_id)
+    }
+
+    pub fn solo_mix(
+        env: Env,
+        owner: Address,
+        recipe_id: u32,
+        token_a: u64,
+        token_b: u64,
+    ) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        if token_a == token_b {
+            return Err(GameError::InvalidInput);
+        }
+        let cfg = config::get(&env);
+        let recipe = get_playable_recipe(&env, recipe_id)?;
+        badges::ensure_holds(&env, &owner, badges::recipe_gate(&env, recipe_id))?;
+        if util::owner_of(&env, &cfg.tea_nft, token_a) != owner
+            || util::owner_of(&env, &cfg.tea_nft, token_b) != owner
+        {
+            return Err(GameError::NotOwner);
+        }
+        ensure_playable_tea(&env, &cfg, token_a)?;
+        ensure_playable_tea(&env, &cfg, token_b)?;
+        let parent_a = util::get_tea_metadata(&env, &cfg.tea_nft, token_a);
+        let parent_b = util::get_tea_metadata(&env, &cfg.tea_nft, token_b);
+        ensure_recipe_accepts(
+            &env,
+            recipe_id,
+            &vec![&env, parent_a.clone(), parent_b.clone()],
+        )?;
+        breeding::ensure_can_breed(&env, token_a, recipe_id)?;
+        breeding::ensure_can_breed(&env, token_b, recipe_id)?;
+        genealogy::ensure_recorded(&env, token_a, &parent_a);
+        track_freshness(&env, &cfg, token_a);
+        genealogy::ensure_recorded(&env, token_b, &parent_b);
+        track_freshness(&env, &cfg, token_b);
+        let recipe = discover_hidden(
+            &env,
+            &cfg,
+            &vec![&env, parent_a.clone(), parent_b.clone()],
+            &owner,
+        )
+        .unwrap_or(recipe);
+
+        charge_recipe_cost(&env, &cfg, &owner, &recipe);
+
+        util::burn_tea(&env, &cfg.tea_nft, &owner, token_a);
+        util::burn_tea(&env, &cfg.tea_nft, &owner, token_b);
+        profiles::record_burned(&env, &owner, 2);
+
+        let mut lineage = Vec::new(&env);
+        lineage.push_back(token_a);
+        lineage.push_back(token_b);
+        let metadata = compose_metadata(
+            &env,
+            &recipe,
+            lineage.clone(),
+            recipe.flavor_profile.clone(),
+        );
+        let new_token_id = mint_tea(&env, &cfg, &owner, metadata.clone());
+        record_offspring(
+            &env,
+            recipe.id,
+            &lineage,
+            false,
+            &[(new_token_id, &metadata)],
+        );
+        salvage::record_investment(&env, new_token_id, recipe.balls_cost);
+        record_activity(
+            &env,
+            &cfg,
+            &owner,
+            HistoryKind::Mix,
+            Some(new_token_id),
+            recipe.balls_cost.max(0),
+            recipe.stars_cost.max(0),
+        );
+        track_progress(&env, &cfg, &owner, QuestAction::Mix(recipe.id));
+        env.events().publish(
+            ("solo_mixed",),
+            (owner, recipe.id, token_a, token_b, new_token_id),
+        );
+        Ok(new_token_id)
+    }
+
+    pub fn set_recipe_slots(
+        env: Env,
+        recipe_id: u32,
+        slots: Vec<IngredientSlot>,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        if !slots.is_empty()
+            && (slots.len() < sessions::MIN_SLOTS || slots.len() > sessions::MAX_SLOTS)
+        {
+            return Err(GameError::InvalidInput);
+        }
+        sessions::set_slots(&env, recipe_id, &slots);
+        env.events()
+            .publish(("recipe_slots_set",), (recipe_id, slots.len()));
+        Ok(())
+    }
+
+    /// Ingredient slots of a recipe; empty while the recipe is hidden and undiscovered.
+    pub fn recipe_slots(env: Env, recipe_id: u32) -> Vec<IngredientSlot> {
+        if is_locked(&env, recipe_id) {
+            return Vec::new(&env);
+        }
+        sessions::slots(&env, recipe_id)
+    }
+
+    pub fn open_mix_session(
+        env: Env,
+        creator: Address,
+        recipe_id: u32,
+        deadline: u64,
+    ) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &creator)?;
+        let _ = get_playable_recipe(&env, recipe_id)?;
+        badges::ensure_holds(&env, &creator, badges::recipe_gate(&env, recipe_id))?;
+        let slots = sessions::slots(&env, recipe_id);
+        if slots.is_empty() {
+            return Err(GameError::InvalidInput);
+        }
+        let now = env.ledger().timestamp();
+        if deadline <= now {
+            return Err(GameError::InvalidInput);
+        }
+
+        let session_id = sessions::next_id(&env);
+        let session = MixSession {
+            creator: creator.clone(),
+            recipe_id,
+            slots,
+            contributions: Vec::new(&env),
+            status: SessionStatus::Collecting,
+            created_at: now,
+            deadline,
+        };
+        sessions::put(&env, session_id, &session);
+        env.events()
+            .publish(("session_opened",), (creator, session_id, recipe_id));
+        Ok(session_id)
+    }
+
+    /// Escrows a tea into a session slot. Returns the child token id once the last slot is filled.
+    pub fn contribute_to_session(
+        env: Env,
+        session_id: u64,
+        contributor: Address,
+        slot: u32,
+        token_id: u64,
+    ) -> Result<Option<u64>, GameError> {
+        ensure_authorized_player(&env, &contributor)?;
+        let cfg = config::get(&env);
+        let mut session = sessions::get(&env, session_id)?;
+        if session.status != SessionStatus::Collecting {
+            return Err(GameError::OfferClosed);
+        }
+        if env.ledger().timestamp() > session.deadline {
+            return Err(GameError::Expired);
+        }
+        let recipe = get_recipe(&env, session.recipe_id)?;
+        badges::ensure_holds(&env, &contributor, badges::recipe_gate(&env, recipe.id))?;
+        ensure_playable_tea(&env, &cfg, token_id)?;
+        let ingredient = session.slots.get(slot).ok_or(GameError::InvalidInput)?;
+        if sessions::slot_taken(&session, slot) {
+            return Err(GameError::InvalidInput);
+        }
+        if util::owner_of(&env, &cfg.tea_nft, token_id) != contributor {
+            return Err(GameError::NotOwner);
+        }
+        let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+        sessions::ensure_fits(&ingredient, &metadata)?;
+        breeding::ensure_can_breed(&env, token_id, session.recipe_id)?;
+        genealogy::ensure_recorded(&env, token_id, &metadata);
+        track_freshness(&env, &cfg, token_id);
+
+        // Each contributor pays an equal share of the recipe cost. Whoever fills the last
+        // slot also pays the rounding remainder, which goes straight to the treasury.
+        let slot_count = session.slots.len() as i128;
+        let fee_balls = recipe.balls_cost.max(0) / slot_count;
+        let fee_stars = recipe.stars_cost.max(0) / slot_count;
+        let fills_session = session.contributions.len() + 1 == session.slots.len();
+        let contract_address = env.current_contract_address();
+        util::transfer_tea(
+            &env,
+            &cfg.tea_nft,
+            &contributor,
+            &contract_address,
+            token_id,
+        );
+        if fee_balls > 0 {
+            util::transfer_from(
+                &env,
+                &cfg.balls_token,
+                &contributor,
+                &contract_address,
+                fee_balls,
+            );
+        }
+        if fee_stars > 0 {
+            util::transfer_from(
+                &env,
+                &cfg.stars_token,
+                &contributor,
+                &contract_address,
+                fee_stars,
+            );
+        }
+        if fills_session {
+            for (token, remainder) in [
+                (
+                    &cfg.balls_token,
+                    recipe.balls_cost.max(0) - fee_balls * slot_count,
+                ),
+                (
+                    &cfg.stars_token,
+                    recipe.stars_cost.max(0) - fee_stars * slot_count,
+                ),
+            ] {
+                if remainder > 0 {
+                    util::transfer_from(&env, token, &contributor, &cfg.treasury, remainder);
+                }
+            }
+        }
+
+        session.contributions.push_back(Contribution {
+            contributor: contributor.clone(),
+            slot,
+            token_id,
+            fee_balls,
+            fee_stars,
+        });
+        env.events().publish(
+            ("session_contributed",),
+            (contributor, session_id, slot, token_id),
+        );
+
+        if !fills_session {
+            sessions::put(&env, session_id, &session);
+            return Ok(None);
+        }
+        let new_token_id = StellarTeaGame::resolve_session(env.clone(), session_id, session)?;
+        Ok(Some(new_token_id))
+    }
+
+    pub fn cancel_mix_session(env: Env, caller: Address, session_id: u64) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &caller)?;
+        let cfg = config::get(&env);
+        let mut session = sessions::get(&env, session_id)?;
+        if session.status != SessionStatus::Collecting {
+            return Err(GameError::OfferClosed);
+        }
+        if caller != session.creator && env.ledger().timestamp() <= session.deadline {
+            return Err(GameError::Unauthorized);
+        }
+
+        let contract_address = env.current_contract_address();
+        for contribution in session.contributions.iter() {
+            util::transfer_tea(
+                &env,
+                &cfg.tea_nft,
+                &contract_address,
+                &contribution.contributor,
+                contribution.token_id,
+            );
+            if contribution.fee_balls > 0 {
+                util::transfer(
+                    &env,
+                    &cfg.balls_token,
+                    &contract_address,
+                    &contribution.contributor,
+                    contribution.fee_balls,
+                );
+            }
+            if contribution.fee_stars > 0 {
+                util::transfer(
+                    &env,
+                    &cfg.stars_token,
+                    &contract_address,
+                    &contribution.contributor,
+                    contribution.fee_stars,
+                );
+            }
+        }
+
+        session.status = SessionStatus::Cancelled;
+        sessions::put(&env, session_id, &session);
+        env.events()
+            .publish(("session_cancelled",), (caller, session_id));
+        Ok(())
+    }
+
+    pub fn get_mix_session(env: Env, session_id: u64) -> Result<MixSession, GameError> {
+        sessions::get(&env, session_id)
+    }
+
+    /// Caps how many times a surviving tea may be mixed into this recipe. Zero removes
+    /// the cap.
+    pub fn set_recipe_max_breeds(
+        env: Env,
+        recipe_id: u32,
+        max_breeds: u32,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        breeding::set_max_breeds(&env, recipe_id, max_breeds);
+        env.events()
+            .publish(("recipe_max_breeds_set",), (recipe_id, max_breeds));
+        Ok(())
+    }
+
+    pub fn tea_breeding(env: Env, token_id: u64) -> BreedRecord {
+        breeding::record(&env, token_id)
+    }
+
+    pub fn set_freshness_config(env: Env, config: FreshnessConfig) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if config.floor > freshness::MAX_FRESHNESS || config.refresh_price < 0 {
+            return Err(GameError::InvalidInput);
+        }
+        freshness::set_config(&env, &config);
+        Ok(())
+    }
+
+    pub fn freshness_config(env: Env) -> Option<FreshnessConfig> {
+        freshness::config(&env)
+    }
+
+    pub fn tea_freshness(env: Env, token_id: u64) -> u32 {
+        let cfg = config::get(&env);
+        freshness::at(&env, freshness_since(&env, &cfg, token_id))
+    }
+
+    /// Stats as they currently count in stat-based mechanics, after freshness decay.
+    pub fn effective_stats(env: Env, token_id: u64) -> TeaStats {
+        let cfg = config::get(&env);
+        let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+        let freshness = freshness::at(&env, freshness_since(&env, &cfg, token_id));
+        freshness::scale(&metadata.stats, freshness)
+    }
+
+    /// Restores a tea to full freshness for BALLS, returning the amount charged.
+    pub fn refresh_tea(env: Env, owner: Address, token_id: u64) -> Result<i128, GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        let cfg = config::get(&env);
+        if util::owner_of(&env, &cfg.tea_nft, token_id) != owner {
+            return Err(GameError::NotOwner);
+        }
+        ensure_playable_tea(&env, &cfg, token_id)?;
+        track_freshness(&env, &cfg, token_id);
+        let cost = freshness::refresh_cost(&env, token_id);
+        collect_charge(&env, &cfg, &cfg.balls_token, &owner, cost);
+        freshness::touch(&env, token_id);
+        env.events()
+            .publish(("tea_refreshed",), (owner, token_id, cost));
+        Ok(cost)
+    }
+
+    pub fn pedigree(env: Env, token_id: u64) -> Option<Pedigree> {
+        genealogy::get(&env, token_id)
+    }
+
+    pub fn ancestors(env: Env, token_id: u64, depth: u32) -> Vec<u64> {
+        genealogy::ancestors(&env, token_id, depth)
+    }
+
+    pub fn descendants(env: Env, token_id: u64) -> Vec<u64> {
+        genealogy::descendants(&env, token_id)
+    }
+
+    pub fn set_recipe_brew_time(env: Env, recipe_id: u32, duration: u64) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        brewing::set_brew_time(&env, recipe_id, duration);
+        env.events()
+            .publish(("recipe_brew_time_set",), (recipe_id, duration));
+        Ok(())
+    }
+
+    /// Escrows the ingredient teas and starts a timed brew of a recipe. Recipes with
+    /// ingredient slots take one tea per slot, in slot order.
+    pub fn start_brew(
+        env: Env,
+        brewer: Address,
+        recipe_id: u32,
+        ingredients: Vec<u64>,
+    ) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &brewer)?;
+        let cfg = config::get(&env);
+        let recipe = get_playable_recipe(&env, recipe_id)?;
+        badges::ensure_holds(&env, &brewer, badges::recipe_gate(&env, recipe_id))?;
+        let duration = brewing::brew_time(&env, recipe_id).ok_or(GameError::InvalidInput)?;
+        if ingredients.is_empty() || ingredients.len() > brewing::MAX_INGREDIENTS {
+            return Err(GameError::InvalidInput);
+        }
+
+        let recipe_slots = sessions::slots(&env, recipe_id);
+        if !recipe_slots.is_empty() && recipe_slots.len() != ingredients.len() {
+            return Err(GameError::InvalidInput);
+        }
+        let mut parents = Vec::new(&env);
+        for (index, token_id) in ingredients.iter().enumerate() {
+            if ingredients.first_index_of(token_id) != Some(index as u32) {
+                return Err(GameError::InvalidInput);
+            }
+            if util::owner_of(&env, &cfg.tea_nft, token_id) != brewer {
+                return Err(GameError::NotOwner);
+            }
+            ensure_playable_tea(&env, &cfg, token_id)?;
+            let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+            if let Some(slot) = recipe_slots.get(index as u32) {
+                sessions::ensure_fits(&slot, &metadata)?;
+            }
+            breeding::ensure_can_breed(&env, token_id, recipe_id)?;
+            genealogy::ensure_recorded(&env, token_id, &metadata);
+            track_freshness(&env, &cfg, token_id);
+            parents.push_back(metadata);
+        }
+        ensure_recipe_accepts(&env, recipe_id, &parents)?;
+
+        charge_recipe_cost(&env, &cfg, &brewer, &recipe);
+        let contract_address = env.current_contract_address();
+        for token_id in ingredients.iter() {
+            util::transfer_tea(&env, &cfg.tea_nft, &brewer, &contract_address, token_id);
+        }
+
+        let now = env.ledger().timestamp();
+        let brew_id = brewing::next_id(&env);
+        let brew = Brew {
+            brewer: brewer.clone(),
+            recipe_id,
+            ingredients,
+            balls_paid: recipe.balls_cost.max(0),
+            started_at: now,
+            ready_at: now + duration,
+        };
+        brewing::put(&env, brew_id, &brew);
+        env.events().publish(
+            ("brew_started",),
+            (brewer, brew_id, recipe_id, brew.ready_at),
+        );
+        Ok(brew_id)
+    }
+
+    /// Burns BALLS to shorten a brew. Only the amount needed to finish is charged.
+    pub fn speed_up_brew(
+        env: Env,
+        brewer: Address,
+        brew_id: u64,
+        balls: i128,
+    ) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &brewer)?;
+        assert_payment(balls)?;
+        let cfg = config::get(&env);
+        let mut brew = brewing::get(&env, brew_id)?;
+        if brew.brewer != brewer {
+            return Err(GameError::Unauthorized);
+        }
+        let remaining = brew.ready_at.saturating_sub(env.ledger().timestamp());
+        let seconds = ((balls / brewing::SPEED_UP_PRICE_PER_SECOND) as u64).min(remaining);
+        if seconds == 0 {
+            return Err(GameError::InvalidInput);
+        }
+        let charged = seconds as i128 * brewing::SPEED_UP_PRICE_PER_SECOND;
+
+        util::burn(&env, &cfg.balls_token, &brewer, charged);
+        brew.ready_at -= seconds;
+        brewing::put(&env, brew_id, &brew);
+        env.events()
+            .publish(("brew_sped_up",), (brewer, brew_id, charged, brew.ready_at));
+        Ok(brew.ready_at)
+    }
+
+    pub fn claim_brew(env: Env, brewer: Address, brew_id: u64) -> Result<u64, GameError> {
+        ensure_authorized_player(&env, &brewer)?;
+        let cfg = config::get(&env);
+        let brew = brewing::get(&env, brew_id)?;
+        if brew.brewer != brewer {
+            return Err(GameError::Unauthorized);
+        }
+        if env.ledger().timestamp() < brew.ready_at {
+            return Err(GameError::NotReady);
+        }
+        let recipe = get_recipe(&env, brew.recipe_id)?;
+
+        let contract_address = env.current_contract_address();
+        for token_id in brew.ingredients.iter() {
+            util::burn_tea(&env, &cfg.tea_nft, &contract_address, token_id);
+        }
+        profiles::record_burned(&env, &brewer, brew.ingredients.len());
+        let metadata = compose_metadata(
+            &env,
+            &recipe,
+            brew.ingredients.clone(),
+            recipe.flavor_profile.clone(),
+        );
+        let new_token_id = mint_tea(&env, &cfg, &brewer, metadata.clone());
+        record_offspring(
+            &env,
+            recipe.id,
+            &brew.ingredients,
+            false,
+            &[(new_token_id, &metadata)],
+        );
+        salvage::record_investment(&env, new_token_id, brew.balls_paid);
+        brewing::remove(&env, brew_id);
+        record_activity(
+            &env,
+            &cfg,
+            &brewer,
+            HistoryKind::Mix,
+            Some(new_token_id),
+            recipe.balls_cost.max(0),
+            recipe.stars_cost.max(0),
+        );
+        track_progress(&env, &cfg, &brewer, QuestAction::Mix(recipe.id));
+        env.events()
+            .publish(("brew_claimed",), (brewer, brew_id, new_token_id));
+        Ok(new_token_id)
+    }
+
+    /// Abandons a brew and hands the escrowed ingredients back. The recipe cost is not
+    /// refunded.
+    pub fn cancel_brew(env: Env, brewer: Address, brew_id: u64) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &brewer)?;
+        let cfg = config::get(&env);
+        let brew = brewing::get(&env, brew_id)?;
+        if brew.brewer != brewer {
+            return Err(GameError::Unauthorized);
+        }
+
+        brewing::remove(&env, brew_id);
+        let contract_address = env.current_contract_address();
+        for token_id in brew.ingredients.iter() {
+            util::transfer_tea(&env, &cfg.tea_nft, &contract_address, &brewer, token_id);
+        }
+        env.events()
+            .publish(("brew_cancelled",), (brewer, brew_id, brew.ingredients));
+        Ok(())
+    }
+
+    pub fn get_brew(env: Env, brew_id: u64) -> Result<Brew, GameError> {
+        brewing::get(&env, brew_id)
+    }
+
+    pub fn upgrade_tea(
+        env: Env,
+        owner: Address,
+        nft_id: u64,
+        balls: i128,
+        stars: i128,
+    ) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        assert_payment(balls)?;
+        assert_payment(stars)?;
+        let cfg = config::get(&env);
+        let token_owner = util::owner_of(&env, &cfg.tea_nft, nft_id);
+        if token_owner != owner {
+            return Err(GameError::NotOwner);
+        }
+        ensure_playable_tea(&env, &cfg, nft_id)?;
+
+        util::transfer_from(
+            &env,
+            &cfg.balls_token,
+            &owner,
+            &env.current_contract_address(),
+            balls,
+        );
+        util::transfer_from(
+            &env,
+            &cfg.stars_token,
+            &owner,
+            &env.current_contract_address(),
+            stars,
+        );
+
+        let burn_balls = balls / 2;
+        let burn_stars = stars / 2;
+        util::burn(
+            &env,
+            &cfg.balls_token,
+            &env.current_contract_address(),
+            burn_balls,
+        );
+        util::burn(
+            &env,
+            &cfg.stars_token,
+            &env.current_contract_address(),
+            burn_stars,
         );
         util::transfer(
             &env,
@@ -619,18 +2410,140 @@ impl StellarTeaGame {
             stars - burn_stars,
         );
 
+        salvage::record_investment(&env, nft_id, balls);
         let mut metadata = util::get_tea_metadata(&env, &cfg.tea_nft, nft_id);
-        metadata.level += UPGRADE_LEVEL_INCREMENT;
-        metadata.rarity += 1;
-        metadata.stats.body += 5;
-        metadata.stats.caffeine += 3;
-        metadata.stats.sweetness += 2;
+        apply_level_up(&mut metadata);
+        let level = metadata.level;
         util::update_tea_metadata(&env, &cfg.tea_nft, nft_id, metadata);
+        record_activity(
+            &env,
+            &cfg,
+            &owner,
+            HistoryKind::Upgrade,
+            Some(nft_id),
+            balls,
+            stars,
+        );
+        track_progress(&env, &cfg, &owner, QuestAction::Upgrade(level));
         env.events()
             .publish(("tea_upgraded",), (owner, nft_id, balls, stars));
         Ok(())
     }
 
+    pub fn tea_experience(env: Env, token_id: u64) -> u64 {
+        experience::get(&env, token_id)
+    }
+
+    /// Burns `sacrifices` and feeds their XP into `target`, levelling it up for free once
+    /// enough XP is banked. Returns the target's new level.
+    pub fn feed_tea(
+        env: Env,
+        owner: Address,
+        target: u64,
+        sacrifices: Vec<u64>,
+    ) -> Result<u32, GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        if sacrifices.is_empty() || sacrifices.len() > experience::MAX_SACRIFICES {
+            return Err(GameError::InvalidInput);
+        }
+        let cfg = config::get(&env);
+        if util::owner_of(&env, &cfg.tea_nft, target) != owner {
+            return Err(GameError::NotOwner);
+        }
+        ensure_playable_tea(&env, &cfg, target)?;
+        let mut xp = 0;
+        for (index, token_id) in sacrifices.iter().enumerate() {
+            if token_id == target || sacrifices.first_index_of(token_id) != Some(index as u32) {
+                return Err(GameError::InvalidInput);
+            }
+            if util::owner_of(&env, &cfg.tea_nft, token_id) != owner {
+                return Err(GameError::NotOwner);
+            }
+            ensure_playable_tea(&env, &cfg, token_id)?;
+            let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+            xp += experience::sacrifice_value(&env, token_id, &metadata);
+            genealogy::ensure_recorded(&env, token_id, &metadata);
+        }
+
+        for token_id in sacrifices.iter() {
+            util::burn_tea(&env, &cfg.tea_nft, &owner, token_id);
+            breeding::clear(&env, token_id);
+            freshness::clear(&env, token_id);
+            experience::clear(&env, token_id);
+            salvage::clear(&env, token_id);
+        }
+        profiles::record_burned(&env, &owner, sacrifices.len());
+
+        let mut metadata = util::get_tea_metadata(&env, &cfg.tea_nft, target);
+        let levels = experience::gain(&env, target, metadata.level, xp);
+        if levels > 0 {
+            for _ in 0..levels {
+                apply_level_up(&mut metadata);
+            }
+            util::update_tea_metadata(&env, &cfg.tea_nft, target, metadata.clone());
+            track_progress(&env, &cfg, &owner, QuestAction::Upgrade(metadata.level));
+        }
+        record_activity(&env, &cfg, &owner, HistoryKind::Upgrade, Some(target), 0, 0);
+        env.events()
+            .publish(("tea_fed",), (owner, target, sacrifices.len(), xp, levels));
+        Ok(metadata.level)
+    }
+
+    pub fn set_salvage_budget(env: Env, amount: i128) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if amount < 0 {
+            return Err(GameError::InvalidInput);
+        }
+        salvage::set_budget(&env, amount);
+        Ok(())
+    }
+
+    pub fn salvage_budget_remaining(env: Env) -> i128 {
+        salvage::remaining(&env)
+    }
+
+    /// BALLS a tea would return if salvaged now, from its rarity and level, capped by the
+    /// BALLS paid into it.
+    pub fn salvage_value(env: Env, token_id: u64) -> i128 {
+        let cfg = config::get(&env);
+        let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+        salvage::value(&metadata, salvage::invested(&env, token_id))
+    }
+
+    pub fn salvage_tea(env: Env, owner: Address, token_id: u64) -> Result<i128, GameError> {
+        ensure_authorized_player(&env, &owner)?;
+        let cfg = config::get(&env);
+        if util::owner_of(&env, &cfg.tea_nft, token_id) != owner {
+            return Err(GameError::NotOwner);
+        }
+        ensure_playable_tea(&env, &cfg, token_id)?;
+        let metadata = util::get_tea_metadata(&env, &cfg.tea_nft, token_id);
+        let payout = salvage::value(&metadata, salvage::invested(&env, token_id));
+        salvage::consume(&env, payout)?;
+        emission::allocate(&env, payout, 0, true)?;
+
+        genealogy::ensure_recorded(&env, token_id, &metadata);
+        util::burn_tea(&env, &cfg.tea_nft, &owner, token_id);
+        profiles::record_burned(&env, &owner, 1);
+        breeding::clear(&env, token_id);
+        freshness::clear(&env, token_id);
+        experience::clear(&env, token_id);
+        salvage::clear(&env, token_id);
+        mint_rewards(&env, &cfg, &owner, payout, 0);
+        record_activity(
+            &env,
+            &cfg,
+            &owner,
+            HistoryKind::Salvage,
+            Some(token_id),
+            payout,
+            0,
+        );
+        env.events()
+            .publish(("tea_salvaged",), (owner, token_id, payout));
+        Ok(payout)
+    }
+
     pub fn list_nft(
         env: Env,
         seller: Address,
@@ -645,6 +2558,7 @@ impl StellarTeaGame {
         if actual_owner != seller {
             return Err(GameError::NotOwner);
         }
+        ensure_playable_tea(&env, &cfg, token_id)?;
 
         util::transfer_tea(
             &env,
@@ -690,6 +2604,9 @@ impl StellarTeaGame {
         ensure_authorized_player(&env, &buyer)?;
         let cfg = config::get(&env);
         let listing = marketplace::get(&env, token_id)?;
+        if listing.seller == buyer {
+            return Err(GameError::InvalidInput);
+        }
 
         let payment_token_address = match listing.payment_token {
             PaymentToken::Balls => cfg.balls_token.clone(),
@@ -704,7 +2621,7 @@ impl StellarTeaGame {
             listing.price,
         );
 
-        let fee = listing.price * MARKET_FEE_BPS / 10_000;
+        let fee = market_fee(listing.price);
         let burn_amount = listing.price * BURN_FEE_BPS / 10_000;
         let seller_amount = listing.price - fee;
         let treasury_amount = fee - burn_amount;
@@ -727,52 +2644,432 @@ impl StellarTeaGame {
             );
         }
 
-        util::transfer(
-            &env,
-            &payment_token_address,
-            &env.current_contract_address(),
-            &listing.seller,
-            seller_amount,
-        );
+        util::transfer(
+            &env,
+            &payment_token_address,
+            &env.current_contract_address(),
+            &listing.seller,
+            seller_amount,
+        );
+
+        util::transfer_tea(
+            &env,
+            &cfg.tea_nft,
+            &env.current_contract_address(),
+            &buyer,
+            token_id,
+        );
+        marketplace::remove(&env, token_id);
+        let level = util::get_tea_metadata(&env, &cfg.tea_nft, token_id).level;
+        award_master_at(&env, &cfg, &buyer, level);
+        let (paid_balls, paid_stars, earned_balls, earned_stars) = match listing.payment_token {
+            PaymentToken::Balls => (listing.price, 0, seller_amount, 0),
+            PaymentToken::Stars => (0, listing.price, 0, seller_amount),
+        };
+        record_activity(
+            &env,
+            &cfg,
+            &buyer,
+            HistoryKind::Purchase,
+            Some(token_id),
+            paid_balls,
+            paid_stars,
+        );
+        record_activity(
+            &env,
+            &cfg,
+            &listing.seller,
+            HistoryKind::Sale,
+            Some(token_id),
+            earned_balls,
+            earned_stars,
+        );
+        track_progress(&env, &cfg, &buyer, QuestAction::Purchase);
+        track_progress(&env, &cfg, &listing.seller, QuestAction::Sell);
+        profiles::record_trade(&env, &buyer, paid_balls, paid_stars);
+        profiles::record_trade(&env, &listing.seller, paid_balls, paid_stars);
+        env.events().publish(
+            ("nft_purchased",),
+            (buyer, listing.seller, token_id, listing.price),
+        );
+        Ok(())
+    }
+
+    pub fn player_history(env: Env, player: Address, cursor: u32, limit: u32) -> Vec<HistoryEntry> {
+        history::page(&env, &player, cursor, limit)
+    }
+
+    pub fn claim_daily(env: Env, player: Address) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        rewards::ensure_claimable(&env, &player)?;
+
+        let cfg = config::get(&env);
+        let limit_symbol = symbol_short!("daily");
+        limits::consume(&env, &player, &limit_symbol, 1)?;
+
+        let multiplier = rewards::record_claim(&env, &player) as i128;
+        let (balls, stars) = emission::allocate(
+            &env,
+            DAILY_BALLS_REWARD * multiplier / 10_000,
+            DAILY_STARS_REWARD * multiplier / 10_000,
+            false,
+        )?;
+        if balls == 0 && stars == 0 {
+            return Err(GameError::LimitExceeded);
+        }
+
+        mint_rewards(&env, &cfg, &player, balls, stars);
+        record_activity(&env, &cfg, &player, HistoryKind::Claim, None, balls, stars);
+        track_progress(&env, &cfg, &player, QuestAction::Claim);
+        env.events()
+            .publish(("daily_claimed",), (player, balls, stars));
+        Ok(())
+    }
+
+    pub fn player_profile(env: Env, player: Address) -> PlayerProfile {
+        profiles::get(&env, &player)
+    }
+
+    pub fn set_nickname(env: Env, player: Address, nickname: String) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        profiles::set_nickname(&env, &player, nickname)
+    }
+
+    pub fn leaderboard_season(env: Env) -> Option<u32> {
+        profiles::season(&env)
+    }
+
+    pub fn leaderboard(env: Env, season: u32, metric: LeaderboardMetric) -> Vec<LeaderboardEntry> {
+        profiles::leaderboard(&env, season, metric)
+    }
+
+    pub fn player_badges(env: Env, player: Address) -> Vec<Badge> {
+        badges::held(&env, &player)
+    }
+
+    pub fn badge_token(env: Env, player: Address, badge: Badge) -> Option<u64> {
+        badges::token_of(&env, &player, badge)
+    }
+
+    /// Restricts a recipe to holders of `badge`; `None` lifts the restriction.
+    pub fn set_recipe_badge_gate(
+        env: Env,
+        recipe_id: u32,
+        badge: Option<Badge>,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = get_recipe(&env, recipe_id)?;
+        badges::set_recipe_gate(&env, recipe_id, badge);
+        env.events()
+            .publish(("recipe_badge_gate_set",), (recipe_id, badge));
+        Ok(())
+    }
+
+    pub fn recipe_badge_gate(env: Env, recipe_id: u32) -> Option<Badge> {
+        badges::recipe_gate(&env, recipe_id)
+    }
+
+    /// Restricts an event to holders of `badge`; `None` lifts the restriction.
+    pub fn set_event_badge_gate(
+        env: Env,
+        event_id: u32,
+        badge: Option<Badge>,
+    ) -> Result<(), GameError> {
+        config::require_admin(&env);
+        let _ = events::get(&env, event_id)?;
+        badges::set_event_gate(&env, event_id, badge);
+        env.events()
+            .publish(("event_badge_gate_set",), (event_id, badge));
+        Ok(())
+    }
+
+    pub fn event_badge_gate(env: Env, event_id: u32) -> Option<Badge> {
+        badges::event_gate(&env, event_id)
+    }
+
+    /// Opens season `season_id` and moves leaderboards along with it. The previous
+    /// season must have ended; its points stay readable under its id.
+    pub fn start_season(env: Env, season_id: u32, season: Season) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if season.ends_at <= season.starts_at
+            || season.premium_price_stars < 0
+            || season.tiers.is_empty()
+            || season.tiers.len() > seasons::MAX_TIERS
+        {
+            return Err(GameError::InvalidInput);
+        }
+        for tier in season.tiers.iter() {
+            if tier.reward_balls < 0 || tier.reward_stars < 0 {
+                return Err(GameError::InvalidInput);
+            }
+            if let Some(recipe_id) = tier.recipe_id {
+                let _ = get_recipe(&env, recipe_id)?;
+            }
+        }
+        seasons::start(&env, season_id, &season)?;
+        env.events().publish(
+            ("season_started",),
+            (season_id, season.starts_at, season.ends_at),
+        );
+        Ok(())
+    }
+
+    pub fn current_season(env: Env) -> Option<u32> {
+        seasons::current_id(&env)
+    }
+
+    pub fn get_season(env: Env, season_id: u32) -> Result<Season, GameError> {
+        seasons::get(&env, season_id)
+    }
+
+    pub fn season_points(env: Env, season_id: u32, player: Address) -> u32 {
+        seasons::points(&env, season_id, &player)
+    }
+
+    pub fn season_premium(env: Env, season_id: u32, player: Address) -> bool {
+        seasons::has_premium(&env, season_id, &player)
+    }
+
+    /// Buys the premium track of the running season with STARS.
+    pub fn buy_season_premium(env: Env, player: Address) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        let cfg = config::get(&env);
+        let (season_id, season) = seasons::active(&env).ok_or(GameError::NotReady)?;
+        if seasons::has_premium(&env, season_id, &player) {
+            return Err(GameError::AlreadyClaimed);
+        }
+        collect_charge(
+            &env,
+            &cfg,
+            &cfg.stars_token,
+            &player,
+            season.premium_price_stars,
+        );
+        seasons::grant_premium(&env, season_id, &player);
+        env.events().publish(
+            ("season_premium_bought",),
+            (player, season_id, season.premium_price_stars),
+        );
+        Ok(())
+    }
+
+    /// Pays out one tier of a season's reward track. Tiers stay claimable after the
+    /// season ends; token rewards must fit today's emission budget in full.
+    pub fn claim_season_tier(
+        env: Env,
+        player: Address,
+        season_id: u32,
+        tier: u32,
+    ) -> Result<Option<u64>, GameError> {
+        ensure_authorized_player(&env, &player)?;
+        let cfg = config::get(&env);
+        let reward = seasons::claim_tier(&env, season_id, &player, tier)?;
+        emission::allocate(&env, reward.reward_balls, reward.reward_stars, true)?;
+        mint_rewards(
+            &env,
+            &cfg,
+            &player,
+            reward.reward_balls,
+            reward.reward_stars,
+        );
+        let token_id = match reward.recipe_id {
+            Some(recipe_id) => {
+                let recipe = get_recipe(&env, recipe_id)?;
+                let metadata =
+                    compose_metadata(&env, &recipe, Vec::new(&env), recipe.flavor_profile.clone());
+                let token_id = mint_tea(&env, &cfg, &player, metadata.clone());
+                record_offspring(
+                    &env,
+                    recipe_id,
+                    &Vec::new(&env),
+                    false,
+                    &[(token_id, &metadata)],
+                );
+                Some(token_id)
+            }
+            None => None,
+        };
+        env.events().publish(
+            ("season_tier_claimed",),
+            (player, season_id, tier, token_id),
+        );
+        Ok(token_id)
+    }
+
+    pub fn define_quest(env: Env, quest_id: u32, quest: Quest) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if quest.target == 0
+            || quest.ends_at <= quest.starts_at
+            || quest.reward_balls < 0
+            || quest.reward_stars < 0
+        {
+            return Err(GameError::InvalidInput);
+        }
+        quests::define(&env, quest_id, &quest)?;
+        env.events().publish(
+            ("quest_defined",),
+            (quest_id, quest.starts_at, quest.ends_at),
+        );
+        Ok(())
+    }
+
+    pub fn get_quest(env: Env, quest_id: u32) -> Result<Quest, GameError> {
+        quests::get(&env, quest_id)
+    }
+
+    pub fn quest_ids(env: Env) -> Vec<u32> {
+        quests::ids(&env)
+    }
+
+    pub fn quest_progress(env: Env, player: Address, quest_id: u32) -> QuestProgress {
+        quests::progress(&env, &player, quest_id)
+    }
+
+    /// Pays out a completed quest. The reward must fit today's emission budget in full.
+    pub fn claim_quest(env: Env, player: Address, quest_id: u32) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        let cfg = config::get(&env);
+        let quest = quests::claim(&env, &player, quest_id)?;
+        emission::allocate(&env, quest.reward_balls, quest.reward_stars, true)?;
+        mint_rewards(&env, &cfg, &player, quest.reward_balls, quest.reward_stars);
+        env.events().publish(
+            ("quest_claimed",),
+            (player, quest_id, quest.reward_balls, quest.reward_stars),
+        );
+        Ok(())
+    }
+
+    /// Links a new player, one with no recorded activity yet, to their referrer.
+    pub fn register_referrer(
+        env: Env,
+        player: Address,
+        referrer: Address,
+    ) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        if !history::is_empty(&env, &player) {
+            return Err(GameError::Unauthorized);
+        }
+        referrals::register(&env, &player, &referrer)?;
+        env.events()
+            .publish(("referrer_registered",), (player, referrer));
+        Ok(())
+    }
+
+    pub fn referrer_of(env: Env, player: Address) -> Option<Address> {
+        referrals::referrer(&env, &player)
+    }
+
+    pub fn referral_stats(env: Env, referrer: Address) -> ReferralStats {
+        referrals::stats(&env, &referrer)
+    }
+
+    pub fn set_referral_bonus(env: Env, bps: u32) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if bps > referrals::MAX_BONUS_BPS {
+            return Err(GameError::InvalidInput);
+        }
+        referrals::set_bonus_bps(&env, bps);
+        Ok(())
+    }
+
+    pub fn set_voucher_signer(env: Env, key: BytesN<32>) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if key == BytesN::from_array(&env, &[0; 32]) {
+            return Err(GameError::InvalidInput);
+        }
+        vouchers::set_signer(&env, &key);
+        env.events().publish(("voucher_signer_set",), (key,));
+        Ok(())
+    }
 
-        util::transfer_tea(
+    pub fn voucher_signer(env: Env) -> Option<BytesN<32>> {
+        vouchers::signer(&env)
+    }
+
+    pub fn voucher_used(env: Env, player: Address, nonce: u64) -> bool {
+        vouchers::is_used(&env, &player, nonce)
+    }
+
+    /// Pays out a reward signed off-chain by the registered voucher signer. Each nonce
+    /// redeems once per player, and the full amounts must fit today's emission budget.
+    pub fn redeem_voucher(
+        env: Env,
+        player: Address,
+        amount_balls: i128,
+        amount_stars: i128,
+        nonce: u64,
+        expiry: u64,
+        signature: BytesN<64>,
+    ) -> Result<(), GameError> {
+        ensure_authorized_player(&env, &player)?;
+        if amount_balls < 0 || amount_stars < 0 {
+            return Err(GameError::InvalidInput);
+        }
+        vouchers::redeem(
             &env,
-            &cfg.tea_nft,
-            &env.current_contract_address(),
-            &buyer,
-            token_id,
+            &player,
+            amount_balls,
+            amount_stars,
+            nonce,
+            expiry,
+            &signature,
+        )?;
+        let cfg = config::get(&env);
+        emission::allocate(&env, amount_balls, amount_stars, true)?;
+        mint_rewards(&env, &cfg, &player, amount_balls, amount_stars);
+        record_activity(
+            &env,
+            &cfg,
+            &player,
+            HistoryKind::Voucher,
+            None,
+            amount_balls,
+            amount_stars,
         );
-        marketplace::remove(&env, token_id);
         env.events().publish(
-            ("nft_purchased",),
-            (buyer, listing.seller, token_id, listing.price),
+            ("voucher_redeemed",),
+            (player, nonce, amount_balls, amount_stars),
         );
         Ok(())
     }
 
-    pub fn claim_daily(env: Env, player: Address) -> Result<(), GameError> {
-        ensure_authorized_player(&env, &player)?;
-        rewards::ensure_claimable(&env, &player)?;
+    pub fn set_emission_schedule(env: Env, schedule: EmissionSchedule) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if schedule.balls_per_day < 0 || schedule.stars_per_day < 0 {
+            return Err(GameError::InvalidInput);
+        }
+        emission::set_schedule(&env, &schedule);
+        Ok(())
+    }
 
-        let cfg = config::get(&env);
-        let limit_symbol = symbol_short!("daily");
-        limits::consume(&env, &player, &limit_symbol, 1)?;
+    pub fn emission_schedule(env: Env) -> Option<EmissionSchedule> {
+        emission::schedule(&env)
+    }
 
-        let daily_cap = config::daily_cap(&env).unwrap_or(i128::MAX);
-        if DAILY_BALLS_REWARD > daily_cap {
-            return Err(GameError::LimitExceeded);
-        }
+    pub fn emission_remaining(env: Env) -> EmissionBudget {
+        emission::remaining(&env)
+    }
 
-        util::mint(&env, &cfg.balls_token, &player, DAILY_BALLS_REWARD);
-        util::mint(&env, &cfg.stars_token, &player, DAILY_STARS_REWARD);
-        rewards::record_claim(&env, &player);
-        env.events().publish(
-            ("daily_claimed",),
-            (player, DAILY_BALLS_REWARD, DAILY_STARS_REWARD),
-        );
+    pub fn set_streak_config(env: Env, config: StreakConfig) -> Result<(), GameError> {
+        config::require_admin(&env);
+        if config.interval == 0
+            || config.multipliers.len() > rewards::MAX_MULTIPLIERS
+            || config.multipliers.iter().any(|bps| bps == 0)
+        {
+            return Err(GameError::InvalidInput);
+        }
+        rewards::set_config(&env, &config);
         Ok(())
     }
 
+    pub fn streak_config(env: Env) -> StreakConfig {
+        rewards::config(&env)
+    }
+
+    pub fn streak(env: Env, player: Address) -> StreakStatus {
+        rewards::streak(&env, &player)
+    }
+
     pub fn join_event(
         env: Env,
         player: Address,
@@ -784,6 +3081,7 @@ impl StellarTeaGame {
         let cfg = config::get(&env);
         let mut event = events::get(&env, event_id)?;
         events::ensure_active(&event, &env)?;
+        badges::ensure_holds(&env, &player, badges::event_gate(&env, event_id))?;
         if stake < event.stake {
             return Err(GameError::InsufficientPayment);
         }
@@ -795,9 +3093,13 @@ impl StellarTeaGame {
             &env.current_contract_address(),
             stake,
         );
+        let first_join = !event.participants.contains(&player);
         event.participants.push_back(player.clone());
         event.reward_pool += stake;
         events::set(&env, event_id, &event);
+        if first_join {
+            seasons::add_points(&env, &player, seasons::EVENT_POINTS);
+        }
         env.events()
             .publish(("event_joined",), (player, event_id, stake));
         Ok(())
@@ -886,3 +3188,915 @@ impl StellarTeaGame {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract, contractimpl, contracttype,
+        testutils::{Address as _, Ledger},
+        Address, Env, String,
+    };
+
+    use super::*;
+
+    #[derive(Clone)]
+    #[contracttype]
+    enum MockKey {
+        Balance(Address),
+        Owner(u64),
+        Metadata(u64),
+        Soulbound(u64),
+        Sequence,
+    }
+
+    /// Token stand-in that only tracks balances. Spending more than a balance traps.
+    #[contract]
+    struct MockToken;
+
+    fn adjust_balance(env: &Env, owner: &Address, delta: i128) {
+        let key = MockKey::Balance(owner.clone());
+        let balance = env
+            .storage()
+            .instance()
+            .get::<MockKey, i128>(&key)
+            .unwrap_or(0)
+            + delta;
+        assert!(balance >= 0, "insufficient balance");
+        env.storage().instance().set(&key, &balance);
+    }
+
+    #[contractimpl]
+    impl MockToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            adjust_balance(&env, &to, amount);
+        }
+
+        pub fn burn(env: Env, from: Address, amount: i128) {
+            adjust_balance(&env, &from, -amount);
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            adjust_balance(&env, &from, -amount);
+            adjust_balance(&env, &to, amount);
+        }
+
+        pub fn transfer_from(
+            env: Env,
+            _spender: Address,
+            from: Address,
+            to: Address,
+            amount: i128,
+        ) {
+            Self::transfer(env, from, to, amount);
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage()
+                .instance()
+                .get::<MockKey, i128>(&MockKey::Balance(id))
+                .unwrap_or(0)
+        }
+    }
+
+    /// Tea collection stand-in. Transfers and burns trap unless `from` owns the tea.
+    #[contract]
+    struct MockTea;
+
+    #[contractimpl]
+    impl MockTea {
+        pub fn mint(env: Env, _caller: Address, to: Address, metadata: TeaMetadata) -> u64 {
+            let token_id = env
+                .storage()
+                .instance()
+                .get::<MockKey, u64>(&MockKey::Sequence)
+                .unwrap_or(0)
+                + 1;
+            env.storage().instance().set(&MockKey::Sequence, &token_id);
+            env.storage().instance().set(&MockKey::Owner(token_id), &to);
+            env.storage()
+                .instance()
+                .set(&MockKey::Metadata(token_id), &metadata);
+            token_id
+        }
+
+        pub fn mint_soulbound(
+            env: Env,
+            caller: Address,
+            to: Address,
+            metadata: TeaMetadata,
+        ) -> u64 {
+            let token_id = Self::mint(env.clone(), caller, to, metadata);
+            env.storage()
+                .instance()
+                .set(&MockKey::Soulbound(token_id), &true);
+            token_id
+        }
+
+        pub fn set_metadata(env: Env, _caller: Address, token_id: u64, metadata: TeaMetadata) {
+            env.storage()
+                .instance()
+                .set(&MockKey::Metadata(token_id), &metadata);
+        }
+
+        pub fn burn_token(env: Env, _caller: Address, owner: Address, token_id: u64) {
+            assert_eq!(Self::owner(env.clone(), token_id), owner);
+            env.storage().instance().remove(&MockKey::Owner(token_id));
+        }
+
+        pub fn get_metadata(env: Env, token_id: u64) -> TeaMetadata {
+            env.storage()
+                .instance()
+                .get::<MockKey, TeaMetadata>(&MockKey::Metadata(token_id))
+                .unwrap()
+        }
+
+        pub fn is_soulbound(env: Env, token_id: u64) -> bool {
+            env.storage().instance().has(&MockKey::Soulbound(token_id))
+        }
+
+        pub fn minted_at(_env: Env, _token_id: u64) -> Option<u64> {
+            None
+        }
+
+        pub fn owner(env: Env, token_id: u64) -> Address {
+            env.storage()
+                .instance()
+                .get::<MockKey, Address>(&MockKey::Owner(token_id))
+                .expect("burned")
+        }
+
+        pub fn exists(env: Env, token_id: u64) -> bool {
+            env.storage().instance().has(&MockKey::Owner(token_id))
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, token_id: u64) {
+            assert_eq!(Self::owner(env.clone(), token_id), from);
+            env.storage().instance().set(&MockKey::Owner(token_id), &to);
+        }
+    }
+
+    struct Game {
+        env: Env,
+        client: StellarTeaGameClient<'static>,
+        tea: MockTeaClient<'static>,
+        balls: MockTokenClient<'static>,
+        treasury: Address,
+    }
+
+    impl Game {
+        fn new() -> Self {
+            let env = Env::default();
+            env.mock_all_auths();
+            let balls = env.register(MockToken, ());
+            let stars = env.register(MockToken, ());
+            let tea = env.register(MockTea, ());
+            let treasury = Address::generate(&env);
+            let game = env.register(
+                StellarTeaGame,
+                (
+                    Address::generate(&env),
+                    treasury.clone(),
+                    balls.clone(),
+                    stars,
+                    tea.clone(),
+                    None::<Address>,
+                ),
+            );
+            Game {
+                client: StellarTeaGameClient::new(&env, &game),
+                tea: MockTeaClient::new(&env, &tea),
+                balls: MockTokenClient::new(&env, &balls),
+                treasury,
+                env,
+            }
+        }
+
+        fn player(&self, balls: i128) -> Address {
+            let player = Address::generate(&self.env);
+            self.balls.mint(&player, &balls);
+            player
+        }
+
+        fn brew(&self, owner: &Address, profile: &str, level: u32) -> u64 {
+            self.tea
+                .mint(owner, owner, &leaf(&self.env, profile, level))
+        }
+
+        fn recipe(&self, recipe_id: u32, balls_cost: i128) {
+            self.client.upsert_recipe(
+                &recipe_id,
+                &String::from_str(&self.env, "Blend"),
+                &String::from_str(&self.env, "blend"),
+                &1,
+                &2,
+                &balls_cost,
+                &0,
+                &base_stats(),
+                &String::from_str(&self.env, "ipfs://blend"),
+            );
+        }
+
+        fn offer(
+            &self,
+            owner: &Address,
+            recipe_id: u32,
+            token_id: u64,
+            fee: i128,
+            mode: OfferMode,
+        ) -> u64 {
+            self.client.create_mix_offer(
+                owner,
+                &recipe_id,
+                &token_id,
+                &String::from_str(&self.env, ""),
+                &0,
+                &fee,
+                &0,
+                &1_000_000,
+                &mode,
+            )
+        }
+    }
+
+    fn leaf(env: &Env, profile: &str, level: u32) -> TeaMetadata {
+        TeaMetadata {
+            display_name: String::from_str(env, "Leaf"),
+            flavor_profile: String::from_str(env, profile),
+            rarity: 1,
+            level,
+            infusion: String::from_str(env, "base"),
+            stats: TeaStats {
+                sweetness: 5,
+                body: 5,
+                caffeine: 5,
+            },
+            lineage: Vec::new(env),
+            image_uri: String::from_str(env, "ipfs://leaf"),
+        }
+    }
+
+    fn base_stats() -> TeaStats {
+        TeaStats {
+            sweetness: 10,
+            body: 20,
+            caffeine: 30,
+        }
+    }
+
+    fn stat_tuple(stats: &TeaStats) -> (u32, u32, u32) {
+        (stats.sweetness, stats.body, stats.caffeine)
+    }
+
+    #[test]
+    fn cooperative_mix_gives_each_player_a_varied_child() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        game.client
+            .set_recipe_mix_rules(&1, &MixMode::Cooperative, &true);
+        let owner = game.player(1_000);
+        let partner = game.player(1_000);
+        let tea_a = game.brew(&owner, "floral", 1);
+        let tea_b = game.brew(&partner, "earthy", 1);
+
+        let offer_id = game.offer(&owner, 1, tea_a, 100, OfferMode::Instant);
+        let child = game
+            .client
+            .accept_mix_offer(&offer_id, &partner, &tea_b, &100, &0)
+            .unwrap();
+
+        let receipt = game.client.get_mix_receipt(&offer_id);
+        let partner_child = receipt.partner_child_token_id.unwrap();
+        assert_eq!(receipt.child_token_id, child);
+        assert_eq!(game.tea.owner(&child), owner);
+        assert_eq!(game.tea.owner(&partner_child), partner);
+        assert!(!game.tea.exists(&tea_a) && !game.tea.exists(&tea_b));
+
+        let seed = receipt.seed.to_array();
+        let owner_stats = game.tea.get_metadata(&child).stats;
+        let partner_stats = game.tea.get_metadata(&partner_child).stats;
+        assert_eq!(
+            stat_tuple(&owner_stats),
+            stat_tuple(&vary_stats(&base_stats(), &seed[0..3]))
+        );
+        assert_eq!(
+            stat_tuple(&partner_stats),
+            stat_tuple(&vary_stats(&base_stats(), &seed[3..6]))
+        );
+
+        // Nobody loses a cooperative mix, so the whole fee pool goes to the treasury.
+        assert_eq!(receipt.fees.loser_balls, 0);
+        assert_eq!(game.balls.balance(&game.treasury), 200);
+        assert_eq!(game.balls.balance(&owner), 900);
+        assert_eq!(game.balls.balance(&partner), 900);
+        assert_eq!(game.client.salvage_value(&child), 0);
+    }
+
+    #[test]
+    fn stat_variance_stays_within_bounds() {
+        for roll in 0..=u8::MAX {
+            let varied = vary_stat(10, roll);
+            assert!(varied.abs_diff(10) <= COOP_STAT_VARIANCE);
+        }
+        assert_eq!(vary_stat(10, 0), 10 - COOP_STAT_VARIANCE);
+        assert_eq!(
+            vary_stat(10, 2 * COOP_STAT_VARIANCE as u8),
+            10 + COOP_STAT_VARIANCE
+        );
+        assert_eq!(vary_stat(0, 0), 0);
+    }
+
+    #[test]
+    fn solo_mix_checks_ownership_and_charges_the_recipe() {
+        let game = Game::new();
+        game.recipe(1, 500);
+        let owner = game.player(1_000);
+        let other = game.player(0);
+        let tea_a = game.brew(&owner, "floral", 1);
+        let tea_b = game.brew(&owner, "earthy", 1);
+        let foreign = game.brew(&other, "earthy", 1);
+
+        assert_eq!(
+            game.client.try_solo_mix(&owner, &1, &tea_a, &tea_a),
+            Err(Ok(GameError::InvalidInput))
+        );
+        assert_eq!(
+            game.client.try_solo_mix(&owner, &1, &tea_a, &foreign),
+            Err(Ok(GameError::NotOwner))
+        );
+
+        let child = game.client.solo_mix(&owner, &1, &tea_a, &tea_b);
+        assert_eq!(game.tea.owner(&child), owner);
+        assert!(!game.tea.exists(&tea_a) && !game.tea.exists(&tea_b));
+        assert_eq!(game.tea.owner(&foreign), other);
+
+        // Without a loser to compensate, that share of the cost is burned.
+        assert_eq!(game.balls.balance(&owner), 500);
+        assert_eq!(game.balls.balance(&game.treasury), 100);
+        assert_eq!(game.balls.balance(&game.client.address), 0);
+        assert_eq!(game.client.salvage_value(&child), 250);
+    }
+
+    #[test]
+    fn session_splits_the_recipe_cost_and_compensates_losers() {
+        let game = Game::new();
+        game.recipe(2, 1_001);
+        let open_slot = IngredientSlot {
+            flavor_profile: None,
+            min_rarity: 0,
+        };
+        game.client.set_recipe_slots(
+            &2,
+            &vec![&game.env, open_slot.clone(), open_slot.clone(), open_slot],
+        );
+        let creator = game.player(1_000);
+        let session_id = game.client.open_mix_session(&creator, &2, &1_000);
+
+        let players = [creator, game.player(1_000), game.player(1_000)];
+        let teas: std::vec::Vec<u64> = players
+            .iter()
+            .map(|player| game.brew(player, "floral", 1))
+            .collect();
+        assert_eq!(
+            game.client
+                .try_contribute_to_session(&session_id, &players[0], &0, &teas[1]),
+            Err(Ok(GameError::NotOwner))
+        );
+        assert_eq!(
+            game.client
+                .contribute_to_session(&session_id, &players[0], &0, &teas[0]),
+            None
+        );
+        assert_eq!(
+            game.client
+                .try_contribute_to_session(&session_id, &players[1], &0, &teas[1]),
+            Err(Ok(GameError::InvalidInput))
+        );
+        game.client
+            .contribute_to_session(&session_id, &players[1], &1, &teas[1]);
+        let child = game
+            .client
+            .contribute_to_session(&session_id, &players[2], &2, &teas[2])
+            .unwrap();
+
+        // Each contributor pays 333; the last one also sends the remainder of 2 to the
+        // treasury. Of the 999 pooled, the two losers get 399 each and the treasury the rest.
+        let winner = game.tea.owner(&child);
+        assert!(teas.iter().all(|tea| !game.tea.exists(tea)));
+        assert_eq!(game.balls.balance(&game.treasury), 2 + 201);
+        for (index, player) in players.iter().enumerate() {
+            let remainder = if index == 2 { 2 } else { 0 };
+            let compensation = if *player == winner { 0 } else { 399 };
+            assert_eq!(
+                game.balls.balance(player),
+                1_000 - 333 - remainder + compensation
+            );
+        }
+        assert!(game.client.get_mix_session(&session_id).status == SessionStatus::Completed);
+        assert_eq!(game.client.salvage_value(&child), 100);
+    }
+
+    #[test]
+    fn requirements_cover_profiles_with_distinct_parents() {
+        let env = Env::default();
+        let requirements = RecipeRequirements {
+            profile_a: Some(String::from_str(&env, "floral")),
+            profile_b: Some(String::from_str(&env, "earthy")),
+            infusion: None,
+            min_level: 2,
+        };
+        let floral = leaf(&env, "floral", 2);
+        let earthy = leaf(&env, "earthy", 2);
+
+        assert!(requirements_met(
+            &requirements,
+            &vec![&env, floral.clone(), earthy.clone()]
+        ));
+        assert!(requirements_met(
+            &requirements,
+            &vec![&env, earthy.clone(), floral.clone()]
+        ));
+        // One floral parent cannot fill both profiles while another parent is present.
+        assert!(!requirements_met(
+            &requirements,
+            &vec![&env, floral.clone(), floral.clone()]
+        ));
+        assert!(!requirements_met(
+            &requirements,
+            &vec![&env, floral.clone(), leaf(&env, "earthy", 1)]
+        ));
+
+        let mut infused = requirements.clone();
+        infused.infusion = Some(String::from_str(&env, "jasmine"));
+        assert!(!requirements_met(
+            &infused,
+            &vec![&env, floral.clone(), earthy.clone()]
+        ));
+        let mut jasmine = earthy;
+        jasmine.infusion = String::from_str(&env, "jasmine");
+        assert!(requirements_met(&infused, &vec![&env, floral, jasmine]));
+    }
+
+    #[test]
+    fn resolve_recipe_skips_unmet_and_slot_recipes() {
+        let game = Game::new();
+        for recipe_id in 1..=3 {
+            game.recipe(recipe_id, 0);
+        }
+        game.client.set_recipe_requirements(
+            &2,
+            &Some(RecipeRequirements {
+                profile_a: Some(String::from_str(&game.env, "smoky")),
+                profile_b: None,
+                infusion: None,
+                min_level: 0,
+            }),
+        );
+        let open_slot = IngredientSlot {
+            flavor_profile: None,
+            min_rarity: 0,
+        };
+        game.client.set_recipe_slots(
+            &3,
+            &vec![&game.env, open_slot.clone(), open_slot.clone(), open_slot],
+        );
+        let owner = game.player(0);
+        let tea_a = game.brew(&owner, "floral", 1);
+        let tea_b = game.brew(&owner, "earthy", 1);
+
+        assert_eq!(
+            game.client.resolve_recipe(&tea_a, &tea_b),
+            vec![&game.env, 1]
+        );
+    }
+
+    #[test]
+    fn first_matching_mix_discovers_a_hidden_recipe_once() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        game.recipe(7, 0);
+        game.client.set_recipe_requirements(
+            &7,
+            &Some(RecipeRequirements {
+                profile_a: Some(String::from_str(&game.env, "floral")),
+                profile_b: Some(String::from_str(&game.env, "earthy")),
+                infusion: None,
+                min_level: 0,
+            }),
+        );
+        game.client.set_recipe_hidden(&7, &true);
+        game.client.set_discovery_reward(&50, &0);
+        assert_eq!(
+            game.client.try_set_recipe_requirements(&7, &None),
+            Err(Ok(GameError::InvalidInput))
+        );
+        assert_eq!(game.client.list_recipes().len(), 1);
+        assert!(game.client.recipe_requirements(&7).is_none());
+
+        let owner = game.player(0);
+        let floral = game.brew(&owner, "floral", 1);
+        let earthy = game.brew(&owner, "earthy", 1);
+        let child = game.client.solo_mix(&owner, &1, &floral, &earthy);
+        assert_eq!(game.client.pedigree(&child).unwrap().recipe_id, Some(7));
+        assert_eq!(game.client.recipe_discovery(&7).unwrap().discoverer, owner);
+        assert_eq!(game.balls.balance(&owner), 50);
+        assert_eq!(game.client.list_recipes().len(), 2);
+
+        // Once discovered the recipe is ordinary: the offered recipe is produced and no
+        // further bonus is paid.
+        let rival = game.player(0);
+        let floral = game.brew(&rival, "floral", 1);
+        let earthy = game.brew(&rival, "earthy", 1);
+        let child = game.client.solo_mix(&rival, &1, &floral, &earthy);
+        assert_eq!(game.client.pedigree(&child).unwrap().recipe_id, Some(1));
+        assert_eq!(game.client.recipe_discovery(&7).unwrap().discoverer, owner);
+        assert_eq!(game.balls.balance(&rival), 0);
+        game.client.set_recipe_requirements(&7, &None);
+    }
+
+    #[test]
+    fn keeper_matches_compatible_offers_for_a_cut() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        let owner_a = game.player(1_000);
+        let owner_b = game.player(1_000);
+        let picky = game.player(1_000);
+        let keeper = game.player(0);
+        let offer_a = game.offer(
+            &owner_a,
+            1,
+            game.brew(&owner_a, "floral", 1),
+            1_000,
+            OfferMode::Instant,
+        );
+        let offer_b = game.offer(
+            &owner_b,
+            1,
+            game.brew(&owner_b, "earthy", 1),
+            1_000,
+            OfferMode::Instant,
+        );
+        let picky_offer = game.client.create_mix_offer(
+            &picky,
+            &1,
+            &game.brew(&picky, "floral", 1),
+            &String::from_str(&game.env, "smoky"),
+            &0,
+            &1_000,
+            &0,
+            &1_000_000,
+            &OfferMode::Instant,
+        );
+
+        assert_eq!(
+            game.client.try_match_offers(&keeper, &offer_a, &offer_a),
+            Err(Ok(GameError::InvalidInput))
+        );
+        assert_eq!(
+            game.client.try_match_offers(&owner_a, &offer_a, &offer_b),
+            Err(Ok(GameError::Unauthorized))
+        );
+        assert_eq!(
+            game.client
+                .try_match_offers(&keeper, &offer_a, &picky_offer),
+            Err(Ok(GameError::InvalidInput))
+        );
+
+        game.client.match_offers(&keeper, &offer_a, &offer_b);
+        assert!(game.client.owner_offers(&owner_b).is_empty());
+        assert!(game.client.owner_offers(&owner_a).is_empty());
+
+        // The keeper takes 1% of the 2_000 pooled; the rest is split 80/20.
+        let receipt = game.client.get_mix_receipt(&offer_a);
+        assert_eq!(game.balls.balance(&keeper), 20);
+        assert_eq!(game.balls.balance(&receipt.loser), 1_584);
+        assert_eq!(game.balls.balance(&receipt.winner), 0);
+        assert_eq!(game.balls.balance(&game.treasury), 396);
+    }
+
+    #[test]
+    fn selecting_a_bid_refunds_the_others() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        let owner = game.player(1_000);
+        let offer_id = game.offer(
+            &owner,
+            1,
+            game.brew(&owner, "floral", 1),
+            100,
+            OfferMode::Bidding,
+        );
+        let chosen = game.player(1_000);
+        let outbid = game.player(1_000);
+        let quitter = game.player(1_000);
+        let chosen_tea = game.brew(&chosen, "earthy", 1);
+        let outbid_tea = game.brew(&outbid, "earthy", 1);
+        let quitter_tea = game.brew(&quitter, "earthy", 1);
+
+        assert_eq!(
+            game.client
+                .try_accept_mix_offer(&offer_id, &chosen, &chosen_tea, &100, &0),
+            Err(Ok(GameError::InvalidInput))
+        );
+        assert_eq!(
+            game.client.try_place_mix_bid(
+                &offer_id,
+                &owner,
+                &game.brew(&owner, "earthy", 1),
+                &100,
+                &0
+            ),
+            Err(Ok(GameError::InvalidInput))
+        );
+        game.client
+            .place_mix_bid(&offer_id, &chosen, &chosen_tea, &300, &0);
+        game.client
+            .place_mix_bid(&offer_id, &outbid, &outbid_tea, &200, &0);
+        game.client
+            .place_mix_bid(&offer_id, &quitter, &quitter_tea, &150, &0);
+        assert_eq!(game.balls.balance(&game.client.address), 750);
+
+        game.client.withdraw_mix_bid(&offer_id, &quitter);
+        assert_eq!(game.tea.owner(&quitter_tea), quitter);
+        assert_eq!(game.balls.balance(&quitter), 1_000);
+        assert_eq!(game.client.mix_bids(&offer_id).len(), 2);
+
+        let child = game.client.select_mix_bid(&owner, &offer_id, &chosen);
+        assert_eq!(game.tea.owner(&outbid_tea), outbid);
+        assert_eq!(game.balls.balance(&outbid), 1_000);
+        assert!(game.client.mix_bids(&offer_id).is_empty());
+        assert!(!game.tea.exists(&chosen_tea));
+
+        let receipt = game.client.get_mix_receipt(&offer_id);
+        assert_eq!(receipt.partner, chosen);
+        assert_eq!(receipt.child_token_id, child);
+        assert_eq!(receipt.fees.loser_balls, 320);
+        assert_eq!(receipt.fees.treasury_balls, 80);
+        assert_eq!(game.balls.balance(&game.client.address), 0);
+    }
+
+    #[test]
+    fn deferred_offer_waits_out_the_withdrawal_window() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        let owner = game.player(1_000);
+        let partner = game.player(1_000);
+        let forcer = game.player(1_000);
+        let keeper = game.player(0);
+        let offer_id = game.offer(
+            &owner,
+            1,
+            game.brew(&owner, "floral", 1),
+            100,
+            OfferMode::Deferred,
+        );
+        let forced_id = game.offer(
+            &owner,
+            1,
+            game.brew(&owner, "floral", 1),
+            100,
+            OfferMode::Deferred,
+        );
+        let partner_tea = game.brew(&partner, "earthy", 1);
+
+        assert_eq!(
+            game.client
+                .accept_mix_offer(&offer_id, &partner, &partner_tea, &100, &0),
+            None
+        );
+        game.client.withdraw_from_mix(&offer_id, &partner);
+        assert_eq!(game.tea.owner(&partner_tea), partner);
+        assert_eq!(game.balls.balance(&partner), 1_000);
+
+        game.client
+            .accept_mix_offer(&offer_id, &partner, &partner_tea, &100, &0);
+        game.client.accept_mix_offer(
+            &forced_id,
+            &forcer,
+            &game.brew(&forcer, "earthy", 1),
+            &100,
+            &0,
+        );
+        game.env.ledger().set_timestamp(DEFERRED_WITHDRAWAL_WINDOW);
+        assert_eq!(
+            game.client.try_mix_tea(&owner, &offer_id),
+            Err(Ok(GameError::NotReady))
+        );
+
+        game.env
+            .ledger()
+            .set_timestamp(DEFERRED_WITHDRAWAL_WINDOW + 1);
+        assert_eq!(
+            game.client.try_withdraw_from_mix(&offer_id, &partner),
+            Err(Ok(GameError::Expired))
+        );
+        // Past the window the owner or a keeper may finalise, but not yet the partner.
+        assert_eq!(
+            game.client.try_mix_tea(&partner, &offer_id),
+            Err(Ok(GameError::NotReady))
+        );
+        game.client.mix_tea(&keeper, &offer_id);
+        assert_eq!(game.balls.balance(&keeper), 2);
+
+        assert_eq!(
+            game.client.try_mix_tea(&forcer, &forced_id),
+            Err(Ok(GameError::NotReady))
+        );
+        game.env.ledger().set_timestamp(DEFERRED_FORCE_TIMEOUT + 1);
+        let child = game.client.mix_tea(&forcer, &forced_id);
+        assert_eq!(
+            game.client.get_mix_receipt(&forced_id).child_token_id,
+            child
+        );
+        assert_eq!(game.client.get_mix_receipt(&forced_id).fees.keeper_balls, 0);
+    }
+
+    #[test]
+    fn matched_receipt_is_readable_under_both_offer_ids() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        let owner_a = game.player(1_000);
+        let owner_b = game.player(1_000);
+        let tea_a = game.brew(&owner_a, "floral", 1);
+        let tea_b = game.brew(&owner_b, "earthy", 1);
+        let offer_a = game.offer(&owner_a, 1, tea_a, 1_000, OfferMode::Instant);
+        let offer_b = game.offer(&owner_b, 1, tea_b, 1_000, OfferMode::Instant);
+        game.env.ledger().set_timestamp(42);
+        let child = game
+            .client
+            .match_offers(&game.player(0), &offer_a, &offer_b);
+
+        let receipt = game.client.get_mix_receipt(&offer_a);
+        let counter = game.client.get_mix_receipt(&offer_b);
+        assert_eq!(receipt.seed, counter.seed);
+        assert_eq!(counter.child_token_id, child);
+        assert_eq!(
+            (receipt.owner.clone(), receipt.partner.clone()),
+            (owner_a.clone(), owner_b.clone())
+        );
+        assert_eq!((receipt.token_a_id, receipt.token_b_id), (tea_a, tea_b));
+        assert_eq!(
+            receipt.parent_a.flavor_profile,
+            String::from_str(&game.env, "floral")
+        );
+        assert_eq!(
+            receipt.parent_b.flavor_profile,
+            String::from_str(&game.env, "earthy")
+        );
+        assert_eq!(
+            (receipt.offered_recipe_id, receipt.produced_recipe_id),
+            (1, 1)
+        );
+        assert_eq!(receipt.fees.keeper_balls, 20);
+
+        // The documented seed derivation reproduces the stored seed and the winner.
+        let payload =
+            (42u64, owner_a.clone(), owner_b.clone(), tea_a, tea_b, 1u32).to_xdr(&game.env);
+        let seed = game.env.crypto().sha256(&payload).to_array();
+        assert_eq!(receipt.seed.to_array(), seed);
+        assert_eq!(receipt.seed_timestamp, 42);
+        let expected_winner = if seed[0] & 1 == 0 { owner_a } else { owner_b };
+        assert_eq!(receipt.winner, expected_winner);
+        assert_eq!(game.tea.owner(&child), receipt.winner);
+    }
+
+    #[test]
+    fn brews_speed_up_for_balls_and_cancel_without_refund() {
+        let game = Game::new();
+        game.recipe(1, 100);
+        game.client.set_recipe_brew_time(&1, &600);
+        let brewer = game.player(1_000_000);
+        let tea_a = game.brew(&brewer, "floral", 1);
+        let tea_b = game.brew(&brewer, "earthy", 1);
+
+        let brew_id = game
+            .client
+            .start_brew(&brewer, &1, &vec![&game.env, tea_a, tea_b]);
+        assert_eq!(game.tea.owner(&tea_a), game.client.address);
+        assert_eq!(
+            game.client.try_claim_brew(&brewer, &brew_id),
+            Err(Ok(GameError::NotReady))
+        );
+
+        // Partial seconds are not charged, and nothing beyond the remaining time is.
+        assert_eq!(game.client.speed_up_brew(&brewer, &brew_id, &100_500), 500);
+        assert_eq!(game.client.speed_up_brew(&brewer, &brew_id, &1_000_000), 0);
+        assert_eq!(game.balls.balance(&brewer), 1_000_000 - 100 - 600_000);
+        let child = game.client.claim_brew(&brewer, &brew_id);
+        assert_eq!(game.tea.owner(&child), brewer);
+        assert_eq!(game.client.salvage_value(&child), 50);
+        assert!(!game.tea.exists(&tea_a) && !game.tea.exists(&tea_b));
+
+        let tea_c = game.brew(&brewer, "floral", 1);
+        let brew_id = game.client.start_brew(&brewer, &1, &vec![&game.env, tea_c]);
+        assert_eq!(
+            game.client.try_cancel_brew(&game.player(0), &brew_id),
+            Err(Ok(GameError::Unauthorized))
+        );
+        game.client.cancel_brew(&brewer, &brew_id);
+        assert_eq!(game.tea.owner(&tea_c), brewer);
+        assert_eq!(game.balls.balance(&brewer), 1_000_000 - 200 - 600_000);
+        assert!(game.client.try_get_brew(&brew_id).is_err());
+    }
+
+    #[test]
+    fn surviving_parents_cool_down_and_hit_the_breed_cap() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        game.recipe(2, 0);
+        game.client
+            .set_recipe_mix_rules(&1, &MixMode::Competitive, &false);
+        game.client.set_recipe_max_breeds(&1, &1);
+        let owner = game.player(1_000);
+        let partner = game.player(1_000);
+        let tea_a = game.brew(&owner, "floral", 1);
+        let tea_b = game.brew(&partner, "earthy", 1);
+        let offer_id = game.offer(&owner, 1, tea_a, 100, OfferMode::Instant);
+        game.client
+            .accept_mix_offer(&offer_id, &partner, &tea_b, &100, &0);
+        assert_eq!(game.tea.owner(&tea_a), owner);
+        assert_eq!(game.client.tea_breeding(&tea_a).breed_count, 1);
+
+        let mix_again = |recipe_id: u32| {
+            game.client.try_create_mix_offer(
+                &owner,
+                &recipe_id,
+                &tea_a,
+                &String::from_str(&game.env, ""),
+                &0,
+                &100,
+                &0,
+                &1_000_000,
+                &OfferMode::Instant,
+            )
+        };
+        assert_eq!(mix_again(1), Err(Ok(GameError::NotReady)));
+        game.env.ledger().set_timestamp(breeding::cooldown_for(0));
+        assert_eq!(mix_again(1), Err(Ok(GameError::LimitExceeded)));
+        assert!(mix_again(2).is_ok());
+    }
+
+    #[test]
+    fn mix_referral_bonus_stays_below_the_treasury_share() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        game.client.set_referral_bonus(&referrals::MAX_BONUS_BPS);
+        let referrer = game.player(0);
+        let owner = game.player(1_000);
+        let partner = game.player(1_000);
+        game.client.register_referrer(&owner, &referrer);
+        game.client.register_referrer(&partner, &referrer);
+        let offer_id = game.offer(
+            &owner,
+            1,
+            game.brew(&owner, "floral", 1),
+            1_000,
+            OfferMode::Instant,
+        );
+        game.client.accept_mix_offer(
+            &offer_id,
+            &partner,
+            &game.brew(&partner, "earthy", 1),
+            &1_000,
+            &0,
+        );
+
+        // A wash pair loses only the 400 treasury share; the bonus is half of it at most.
+        assert_eq!(game.balls.balance(&game.treasury), 400);
+        assert_eq!(game.balls.balance(&referrer), 200);
+        assert_eq!(game.client.referral_stats(&referrer).earned_balls, 200);
+    }
+
+    #[test]
+    fn badge_gates_only_apply_to_known_ids() {
+        let game = Game::new();
+        game.recipe(1, 0);
+        assert_eq!(
+            game.client
+                .try_set_recipe_badge_gate(&2, &Some(Badge::FirstMix)),
+            Err(Ok(GameError::OfferNotFound))
+        );
+        assert_eq!(
+            game.client
+                .try_set_event_badge_gate(&1, &Some(Badge::FirstMix)),
+            Err(Ok(GameError::OfferNotFound))
+        );
+
+        game.client
+            .set_recipe_badge_gate(&1, &Some(Badge::FirstMix));
+        let owner = game.player(0);
+        let tea_a = game.brew(&owner, "floral", 1);
+        let tea_b = game.brew(&owner, "earthy", 1);
+        assert_eq!(
+            game.client.try_solo_mix(&owner, &1, &tea_a, &tea_b),
+            Err(Ok(GameError::Unauthorized))
+        );
+        game.client.set_recipe_badge_gate(&1, &None);
+        game.client.solo_mix(&owner, &1, &tea_a, &tea_b);
+        assert_eq!(
+            game.client.player_badges(&owner),
+            vec![&game.env, Badge::FirstMix]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::errors::GameError;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+/// Daily budgets used until the admin sets a schedule.
+pub const DEFAULT_BALLS_PER_DAY: i128 = 10_000_000_000; // 100 with 8 decimals
+pub const DEFAULT_STARS_PER_DAY: i128 = 1_000_000_000; // 10 with 8 decimals
+
+/// Daily reward budgets shared by every minting path. Budgets halve every
+/// `halving_days` days counted from `start_day`; zero disables halving.
+#[derive(Clone)]
+#[contracttype]
+pub struct EmissionSchedule {
+    pub balls_per_day: i128,
+    pub stars_per_day: i128,
+    pub start_day: u64,
+    pub halving_days: u64,
+}
+
+/// Amounts minted so far on `day`.
+#[derive(Clone)]
+#[contracttype]
+pub struct DailyEmission {
+    pub day: u64,
+    pub balls: i128,
+    pub stars: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EmissionBudget {
+    pub day: u64,
+    pub balls_remaining: i128,
+    pub stars_remaining: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Schedule,
+    Minted,
+}
+
+pub fn current_day(env: &Env) -> u64 {
+    env.ledger().timestamp() / SECONDS_PER_DAY
+}
+
+pub fn set_schedule(env: &Env, schedule: &EmissionSchedule) {
+    env.storage().instance().set(&DataKey::Schedule, schedule);
+}
+
+pub fn schedule(env: &Env) -> Option<EmissionSchedule> {
+    env.storage()
+        .instance()
+        .get::<DataKey, EmissionSchedule>(&DataKey::Schedule)
+}
+
+fn halved(amount: i128, halvings: u64) -> i128 {
+    if halvings >= 127 {
+        0
+    } else {
+        amount >> halvings
+    }
+}
+
+/// Budgets for `day`. Without a schedule the default daily budgets apply.
+pub fn budget_for(env: &Env, day: u64) -> (i128, i128) {
+    let Some(schedule) = schedule(env) else {
+        return (DEFAULT_BALLS_PER_DAY, DEFAULT_STARS_PER_DAY);
+    };
+    let halvings = match schedule.halving_days {
+        0 => 0,
+        period => day.saturating_sub(schedule.start_day) / period,
+    };
+    (
+        halved(schedule.balls_per_day, halvings),
+        halved(schedule.stars_per_day, halvings),
+    )
+}
+
+fn minted_today(env: &Env) -> DailyEmission {
+    let day = current_day(env);
+    env.storage()
+        .persistent()
+        .get::<DataKey, DailyEmission>(&DataKey::Minted)
+        .filter(|minted| minted.day == day)
+        .unwrap_or(DailyEmission {
+            day,
+            balls: 0,
+            stars: 0,
+        })
+}
+
+pub fn remaining(env: &Env) -> EmissionBudget {
+    let minted = minted_today(env);
+    let (balls, stars) = budget_for(env, minted.day);
+    EmissionBudget {
+        day: minted.day,
+        balls_remaining: (balls - minted.balls).max(0),
+        stars_remaining: (stars - minted.stars).max(0),
+    }
+}
+
+/// Reserves reward mints against today's budget and returns what may be minted.
+/// Strict callers get `LimitExceeded` unless the full amounts fit; otherwise each
+/// amount is cut down to what remains.
+pub fn allocate(
+    env: &Env,
+    balls: i128,
+    stars: i128,
+    strict: bool,
+) -> Result<(i128, i128), GameError> {
+    let budget = remaining(env);
+    if strict && (balls > budget.balls_remaining || stars > budget.stars_remaining) {
+        return Err(GameError::LimitExceeded);
+    }
+    let balls = balls.clamp(0, budget.balls_remaining);
+    let stars = stars.clamp(0, budget.stars_remaining);
+    if balls == 0 && stars == 0 {
+        return Ok((0, 0));
+    }
+    let mut minted = minted_today(env);
+    minted.balls += balls;
+    minted.stars += stars;
+    env.storage().persistent().set(&DataKey::Minted, &minted);
+    Ok((balls, stars))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Ledger, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn budget_halves_and_pro_rates() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            assert_eq!(allocate(&env, 0, 0, false), Ok((0, 0)));
+            assert!(!env.storage().persistent().has(&DataKey::Minted));
+            assert_eq!(allocate(&env, 500, 50, true), Ok((500, 50)));
+            assert_eq!(remaining(&env).balls_remaining, DEFAULT_BALLS_PER_DAY - 500);
+
+            set_schedule(
+                &env,
+                &EmissionSchedule {
+                    balls_per_day: 1_000,
+                    stars_per_day: 100,
+                    start_day: 0,
+                    halving_days: 7,
+                },
+            );
+            assert_eq!(remaining(&env).balls_remaining, 500);
+            assert_eq!(allocate(&env, 600, 0, true), Err(GameError::LimitExceeded));
+            assert_eq!(allocate(&env, 600, 80, false), Ok((500, 50)));
+            assert_eq!(allocate(&env, 1, 0, false), Ok((0, 0)));
+
+            env.ledger().set_timestamp(7 * SECONDS_PER_DAY);
+            let budget = remaining(&env);
+            assert_eq!((budget.balls_remaining, budget.stars_remaining), (500, 50));
+
+            env.ledger().set_timestamp(14 * SECONDS_PER_DAY);
+            assert_eq!(remaining(&env).balls_remaining, 250);
+        });
+    }
+}
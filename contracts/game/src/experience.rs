@@ -0,0 +1,88 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::tea::TeaMetadata;
+
+/// XP a level-1, rarity-1 tea is worth when sacrificed.
+pub const BASE_XP_VALUE: u64 = 100;
+/// XP needed per current level to reach the next one.
+pub const XP_PER_LEVEL: u64 = 1_000;
+pub const MAX_SACRIFICES: u32 = 5;
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Xp(u64),
+}
+
+pub fn get(env: &Env, token_id: u64) -> u64 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::Xp(token_id))
+        .unwrap_or(0)
+}
+
+fn put(env: &Env, token_id: u64, xp: u64) {
+    if xp == 0 {
+        clear(env, token_id);
+    } else {
+        env.storage().persistent().set(&DataKey::Xp(token_id), &xp);
+    }
+}
+
+pub fn clear(env: &Env, token_id: u64) {
+    env.storage().persistent().remove(&DataKey::Xp(token_id));
+}
+
+/// XP released by sacrificing a tea: a base value scaled by level and rarity, plus
+/// whatever XP the tea had banked.
+pub fn sacrifice_value(env: &Env, token_id: u64, metadata: &TeaMetadata) -> u64 {
+    let level = metadata.level.max(1) as u64;
+    let rarity = metadata.rarity.max(1) as u64;
+    BASE_XP_VALUE * level * rarity + get(env, token_id)
+}
+
+pub fn threshold(level: u32) -> u64 {
+    XP_PER_LEVEL * level.max(1) as u64
+}
+
+/// Banks `xp` on a tea at `level` and returns how many levels it gains. XP spent on
+/// level-ups is deducted; the remainder carries over.
+pub fn gain(env: &Env, token_id: u64, level: u32, xp: u64) -> u32 {
+    let mut banked = get(env, token_id) + xp;
+    let mut level = level;
+    let mut gained = 0;
+    while banked >= threshold(level) {
+        banked -= threshold(level);
+        level += 1;
+        gained += 1;
+    }
+    put(env, token_id, banked);
+    gained
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn xp_carries_over_between_levels() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            assert_eq!(gain(&env, 1, 1, 900), 0);
+            assert_eq!(get(&env, 1), 900);
+            assert_eq!(gain(&env, 1, 1, 2_200), 2);
+            assert_eq!(get(&env, 1), 100);
+            assert_eq!(gain(&env, 1, 3, 2_900), 1);
+            assert_eq!(get(&env, 1), 0);
+        });
+    }
+}
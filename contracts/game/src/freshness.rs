@@ -0,0 +1,172 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::tea::TeaStats;
+
+/// Freshness of a newly minted or fully refreshed tea, in basis points.
+pub const MAX_FRESHNESS: u32 = 10_000;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Decay settings. Freshness drops linearly by `decay_per_day` basis points until it
+/// reaches `floor`; restoring one point costs `refresh_price` BALLS.
+#[derive(Clone)]
+#[contracttype]
+pub struct FreshnessConfig {
+    pub decay_per_day: u32,
+    pub floor: u32,
+    pub refresh_price: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    FreshnessConfig,
+    RefreshedAt(u64),
+}
+
+pub fn set_config(env: &Env, config: &FreshnessConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKey::FreshnessConfig, config);
+}
+
+pub fn config(env: &Env) -> Option<FreshnessConfig> {
+    env.storage()
+        .instance()
+        .get::<DataKey, FreshnessConfig>(&DataKey::FreshnessConfig)
+}
+
+pub fn refreshed_at(env: &Env, token_id: u64) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::RefreshedAt(token_id))
+}
+
+/// Restarts decay for a tea from the current ledger time.
+pub fn touch(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RefreshedAt(token_id), &env.ledger().timestamp());
+}
+
+/// Starts tracking a tea the game has not seen before, with its clock running from
+/// `since` (normally its mint time). Existing clocks are kept.
+pub fn ensure_tracked(env: &Env, token_id: u64, since: u64) {
+    if refreshed_at(env, token_id).is_none() {
+        env.storage()
+            .persistent()
+            .set(&DataKey::RefreshedAt(token_id), &since);
+    }
+}
+
+pub fn clear(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::RefreshedAt(token_id));
+}
+
+/// Freshness of a tea whose clock started at `since`. Without a decay config every
+/// tea is fully fresh.
+pub fn at(env: &Env, since: u64) -> u32 {
+    let Some(config) = config(env) else {
+        return MAX_FRESHNESS;
+    };
+    let elapsed = env.ledger().timestamp().saturating_sub(since);
+    let decayed = elapsed.saturating_mul(config.decay_per_day as u64) / SECONDS_PER_DAY;
+    let floor = config.floor.min(MAX_FRESHNESS);
+    (MAX_FRESHNESS as u64)
+        .saturating_sub(decayed)
+        .max(floor as u64) as u32
+}
+
+/// Current freshness of a tracked tea. The game tracks every tea it mints and starts
+/// the clock of outside teas from their mint time before reading them, so the
+/// fully-fresh fallback only applies to ids it never handled.
+pub fn current(env: &Env, token_id: u64) -> u32 {
+    refreshed_at(env, token_id).map_or(MAX_FRESHNESS, |since| at(env, since))
+}
+
+/// Average freshness over a set of teas.
+pub fn average(env: &Env, token_ids: impl Iterator<Item = u64>) -> u32 {
+    let mut total = 0u64;
+    let mut count = 0u64;
+    for token_id in token_ids {
+        total += current(env, token_id) as u64;
+        count += 1;
+    }
+    if count == 0 {
+        return MAX_FRESHNESS;
+    }
+    (total / count) as u32
+}
+
+/// Scales every stat by `freshness` basis points.
+pub fn scale(stats: &TeaStats, freshness: u32) -> TeaStats {
+    let apply = |value: u32| (value as u64 * freshness as u64 / MAX_FRESHNESS as u64) as u32;
+    TeaStats {
+        sweetness: apply(stats.sweetness),
+        body: apply(stats.body),
+        caffeine: apply(stats.caffeine),
+    }
+}
+
+/// BALLS needed to bring a tracked tea back to full freshness.
+pub fn refresh_cost(env: &Env, token_id: u64) -> i128 {
+    let Some(config) = config(env) else {
+        return 0;
+    };
+    (MAX_FRESHNESS - current(env, token_id)) as i128 * config.refresh_price
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Ledger, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn decays_lazily_to_floor_and_refreshes() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            touch(&env, 1);
+            env.ledger().set_timestamp(SECONDS_PER_DAY);
+            assert_eq!(current(&env, 1), MAX_FRESHNESS);
+
+            set_config(
+                &env,
+                &FreshnessConfig {
+                    decay_per_day: 1_000,
+                    floor: 6_000,
+                    refresh_price: 2,
+                },
+            );
+            assert_eq!(current(&env, 1), 9_000);
+            assert_eq!(current(&env, 2), MAX_FRESHNESS);
+            ensure_tracked(&env, 2, 0);
+            assert_eq!(current(&env, 2), 9_000);
+            assert_eq!(refresh_cost(&env, 1), 2_000);
+
+            env.ledger().set_timestamp(10 * SECONDS_PER_DAY);
+            assert_eq!(current(&env, 1), 6_000);
+            let stats = scale(
+                &TeaStats {
+                    sweetness: 10,
+                    body: 5,
+                    caffeine: 0,
+                },
+                current(&env, 1),
+            );
+            assert_eq!((stats.sweetness, stats.body, stats.caffeine), (6, 3, 0));
+
+            touch(&env, 1);
+            assert_eq!(current(&env, 1), MAX_FRESHNESS);
+            assert_eq!(refresh_cost(&env, 1), 0);
+        });
+    }
+}
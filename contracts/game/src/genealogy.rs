@@ -0,0 +1,223 @@
+use soroban_sdk::{contracttype, Env, String, Vec};
+
+use crate::tea::TeaMetadata;
+
+/// Upper bound on ids returned by a single ancestry or descendant query.
+pub const MAX_RESULTS: u32 = 64;
+
+/// Snapshot of a tea's metadata taken when its pedigree is first recorded.
+#[derive(Clone)]
+#[contracttype]
+pub struct TeaSummary {
+    pub display_name: String,
+    pub flavor_profile: String,
+    pub infusion: String,
+    pub rarity: u32,
+    pub level: u32,
+}
+
+/// Genealogy entry for a tea. It outlives the token, so ancestry stays queryable
+/// after parents are burned. Teas minted outside the game have no recipe and are
+/// generation zero.
+#[derive(Clone)]
+#[contracttype]
+pub struct Pedigree {
+    pub parents: Vec<u64>,
+    pub generation: u32,
+    pub recipe_id: Option<u32>,
+    pub summary: TeaSummary,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Pedigree(u64),
+    Children(u64),
+}
+
+fn summarize(metadata: &TeaMetadata) -> TeaSummary {
+    TeaSummary {
+        display_name: metadata.display_name.clone(),
+        flavor_profile: metadata.flavor_profile.clone(),
+        infusion: metadata.infusion.clone(),
+        rarity: metadata.rarity,
+        level: metadata.level,
+    }
+}
+
+pub fn get(env: &Env, token_id: u64) -> Option<Pedigree> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Pedigree>(&DataKey::Pedigree(token_id))
+}
+
+pub fn generation(env: &Env, token_id: u64) -> u32 {
+    get(env, token_id)
+        .map(|pedigree| pedigree.generation)
+        .unwrap_or(0)
+}
+
+fn children(env: &Env, token_id: u64) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<u64>>(&DataKey::Children(token_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Records a founder tea the game has not minted itself, keeping its own lineage.
+pub fn ensure_recorded(env: &Env, token_id: u64, metadata: &TeaMetadata) {
+    if get(env, token_id).is_some() {
+        return;
+    }
+    let pedigree = Pedigree {
+        parents: metadata.lineage.clone(),
+        generation: 0,
+        recipe_id: None,
+        summary: summarize(metadata),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Pedigree(token_id), &pedigree);
+}
+
+pub fn record_child(
+    env: &Env,
+    token_id: u64,
+    parents: &Vec<u64>,
+    generation: u32,
+    recipe_id: u32,
+    metadata: &TeaMetadata,
+) {
+    let pedigree = Pedigree {
+        parents: parents.clone(),
+        generation,
+        recipe_id: Some(recipe_id),
+        summary: summarize(metadata),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Pedigree(token_id), &pedigree);
+    for parent in parents.iter() {
+        let mut siblings = children(env, parent);
+        siblings.push_back(token_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Children(parent), &siblings);
+    }
+}
+
+/// Ancestors up to `depth` generations back, nearest first.
+pub fn ancestors(env: &Env, token_id: u64, depth: u32) -> Vec<u64> {
+    let mut found = Vec::new(env);
+    let mut frontier = Vec::new(env);
+    frontier.push_back(token_id);
+    for _ in 0..depth {
+        let mut next = Vec::new(env);
+        for id in frontier.iter() {
+            let Some(pedigree) = get(env, id) else {
+                continue;
+            };
+            for parent in pedigree.parents.iter() {
+                if found.len() >= MAX_RESULTS {
+                    return found;
+                }
+                if !found.contains(parent) {
+                    found.push_back(parent);
+                    next.push_back(parent);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+    found
+}
+
+/// All known descendants, nearest first, capped at `MAX_RESULTS`.
+pub fn descendants(env: &Env, token_id: u64) -> Vec<u64> {
+    let mut found = Vec::new(env);
+    let mut cursor = 0;
+    let mut current = token_id;
+    loop {
+        for child in children(env, current).iter() {
+            if found.len() >= MAX_RESULTS {
+                return found;
+            }
+            if !found.contains(child) {
+                found.push_back(child);
+            }
+        }
+        match found.get(cursor) {
+            Some(next) => current = next,
+            None => return found,
+        }
+        cursor += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, vec, Env, String, Vec};
+
+    use super::*;
+    use crate::tea::TeaStats;
+
+    #[contract]
+    struct Dummy;
+
+    fn tea(env: &Env, lineage: Vec<u64>) -> TeaMetadata {
+        TeaMetadata {
+            display_name: String::from_str(env, "Heirloom"),
+            flavor_profile: String::from_str(env, "earthy"),
+            rarity: 1,
+            level: 1,
+            infusion: String::from_str(env, "base"),
+            stats: TeaStats {
+                sweetness: 1,
+                body: 1,
+                caffeine: 1,
+            },
+            lineage,
+            image_uri: String::from_str(env, "ipfs://heirloom"),
+        }
+    }
+
+    #[test]
+    fn pedigree_survives_and_walks_both_ways() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            ensure_recorded(&env, 1, &tea(&env, Vec::new(&env)));
+            ensure_recorded(&env, 2, &tea(&env, Vec::new(&env)));
+            record_child(
+                &env,
+                3,
+                &vec![&env, 1, 2],
+                1,
+                7,
+                &tea(&env, vec![&env, 1, 2]),
+            );
+            ensure_recorded(&env, 4, &tea(&env, Vec::new(&env)));
+            record_child(
+                &env,
+                5,
+                &vec![&env, 3, 4],
+                2,
+                7,
+                &tea(&env, vec![&env, 3, 4]),
+            );
+
+            assert_eq!(generation(&env, 5), 2);
+            assert_eq!(get(&env, 3).unwrap().recipe_id, Some(7));
+            assert_eq!(ancestors(&env, 5, 1), vec![&env, 3, 4]);
+            assert_eq!(ancestors(&env, 5, 5), vec![&env, 3, 4, 1, 2]);
+            assert_eq!(descendants(&env, 1), vec![&env, 3, 5]);
+            assert!(descendants(&env, 5).is_empty());
+        });
+    }
+}
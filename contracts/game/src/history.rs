@@ -0,0 +1,131 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Entries kept per player; older entries are overwritten.
+pub const CAPACITY: u32 = 32;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[contracttype]
+pub enum HistoryKind {
+    Mix,
+    Purchase,
+    Sale,
+    Upgrade,
+    Claim,
+    Salvage,
+    Voucher,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    pub timestamp: u64,
+    pub token_id: Option<u64>,
+    pub amount_balls: i128,
+    pub amount_stars: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Count(Address),
+    Entry(Address, u32),
+}
+
+fn count(env: &Env, player: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::Count(player.clone()))
+        .unwrap_or(0)
+}
+
+/// Whether the player has never done anything recorded in history.
+pub fn is_empty(env: &Env, player: &Address) -> bool {
+    count(env, player) == 0
+}
+
+pub fn record(
+    env: &Env,
+    player: &Address,
+    kind: HistoryKind,
+    token_id: Option<u64>,
+    amount_balls: i128,
+    amount_stars: i128,
+) {
+    let total = count(env, player);
+    let slot = (total % CAPACITY as u64) as u32;
+    let entry = HistoryEntry {
+        kind,
+        timestamp: env.ledger().timestamp(),
+        token_id,
+        amount_balls,
+        amount_stars,
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Entry(player.clone(), slot), &entry);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Count(player.clone()), &(total + 1));
+}
+
+/// Returns up to `limit` entries, newest first, skipping the `cursor` most recent ones.
+pub fn page(env: &Env, player: &Address, cursor: u32, limit: u32) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new(env);
+    let total = count(env, player);
+    let available = total.min(CAPACITY as u64);
+    let end = available.min(cursor as u64 + limit.min(CAPACITY) as u64);
+    for offset in cursor as u64..end {
+        let sequence = total - 1 - offset;
+        let slot = (sequence % CAPACITY as u64) as u32;
+        if let Some(entry) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, HistoryEntry>(&DataKey::Entry(player.clone(), slot))
+        {
+            entries.push_back(entry);
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        Address, Env,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn ring_buffer_keeps_latest_entries() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            for step in 0..(CAPACITY as i128 + 3) {
+                env.ledger().set_timestamp(step as u64);
+                record(&env, &player, HistoryKind::Claim, None, step, 0);
+            }
+
+            let newest = page(&env, &player, 0, 2);
+            assert_eq!(newest.len(), 2);
+            assert_eq!(newest.get(0).unwrap().amount_balls, CAPACITY as i128 + 2);
+            assert_eq!(newest.get(1).unwrap().amount_balls, CAPACITY as i128 + 1);
+
+            let oldest = page(&env, &player, CAPACITY - 1, 10);
+            assert_eq!(oldest.len(), 1);
+            assert_eq!(oldest.get(0).unwrap().amount_balls, 3);
+
+            assert!(page(&env, &player, CAPACITY, 10).is_empty());
+        });
+    }
+}
@@ -1,14 +1,29 @@
 #![no_std]
 
+mod badges;
+mod breeding;
+mod brewing;
 mod config;
+mod emission;
 mod errors;
 mod events;
+mod experience;
+mod freshness;
+mod genealogy;
+mod history;
 mod limits;
 mod marketplace;
 mod mixing;
+mod profiles;
+mod quests;
+mod referrals;
 mod rewards;
+mod salvage;
+mod seasons;
+mod sessions;
 pub mod tea;
 mod util;
+mod vouchers;
 
 mod contract;
 
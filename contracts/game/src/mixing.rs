@@ -1,6 +1,6 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, BytesN, Env, String, Vec};
 
-use crate::errors::GameError;
+use crate::{errors::GameError, tea::TeaMetadata};
 
 #[derive(Clone, PartialEq, Eq)]
 #[contracttype]
@@ -11,6 +11,43 @@ pub enum OfferStatus {
     Cancelled,
 }
 
+/// How an offer finds its partner and resolves. `Instant` offers resolve as soon as a
+/// partner accepts at the owner's fee; `Deferred` offers wait in `ReadyToMix` until
+/// finalised with `mix_tea`; `Bidding` offers collect escrowed bids until the owner
+/// selects one.
+#[derive(Clone, PartialEq, Eq)]
+#[contracttype]
+pub enum OfferMode {
+    Instant,
+    Deferred,
+    Bidding,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+#[contracttype]
+pub enum MixMode {
+    Competitive,
+    Cooperative,
+}
+
+/// Per-recipe resolution rules. Recipes without explicit rules are competitive
+/// and consume both parents.
+#[derive(Clone)]
+#[contracttype]
+pub struct MixRules {
+    pub mode: MixMode,
+    pub consume_parents: bool,
+}
+
+impl MixRules {
+    pub fn standard() -> Self {
+        MixRules {
+            mode: MixMode::Competitive,
+            consume_parents: true,
+        }
+    }
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct MixOffer {
@@ -26,16 +63,70 @@ pub struct MixOffer {
     pub partner_fee_balls: i128,
     pub partner_fee_stars: i128,
     pub status: OfferStatus,
+    pub mode: OfferMode,
+    pub accepted_at: Option<u64>,
     pub created_at: u64,
     pub deadline: u64,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct MixBid {
+    pub bidder: Address,
+    pub token_id: u64,
+    pub fee_balls: i128,
+    pub fee_stars: i128,
+    pub created_at: u64,
+}
+
+pub const MAX_BIDS_PER_OFFER: u32 = 10;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeDistribution {
+    pub keeper_balls: i128,
+    pub keeper_stars: i128,
+    pub loser_balls: i128,
+    pub loser_stars: i128,
+    pub treasury_balls: i128,
+    pub treasury_stars: i128,
+}
+
+/// Audit record of a resolved offer, kept after the offer itself is removed.
+///
+/// `seed` is `sha256(xdr((seed_timestamp, owner, partner, token_a_id, token_b_id,
+/// offered_recipe_id)))`, with the tuple serialised through `ToXdr`. In competitive
+/// mixes the owner wins when the lowest bit of `seed[0]` is clear. Cooperative children
+/// take their stat offsets from `seed[0..3]` (owner) and `seed[3..6]` (partner).
+#[derive(Clone)]
+#[contracttype]
+pub struct MixReceipt {
+    pub owner: Address,
+    pub partner: Address,
+    pub token_a_id: u64,
+    pub token_b_id: u64,
+    pub parent_a: TeaMetadata,
+    pub parent_b: TeaMetadata,
+    pub offered_recipe_id: u32,
+    pub produced_recipe_id: u32,
+    pub mode: MixMode,
+    pub seed_timestamp: u64,
+    pub seed: BytesN<32>,
+    pub winner: Address,
+    pub loser: Address,
+    pub child_token_id: u64,
+    pub partner_child_token_id: Option<u64>,
+    pub fees: FeeDistribution,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     Offer(u64),
     Sequence,
-    OwnerIndex(Address, u32),
+    OwnerOffers(Address),
+    Bids(u64),
+    Receipt(u64),
 }
 
 pub trait BeverageMixer {
@@ -72,22 +163,62 @@ pub fn remove(env: &Env, id: u64) {
     env.storage().persistent().remove(&DataKey::Offer(id));
 }
 
-pub fn set_owner_index(env: &Env, owner: &Address, recipe_id: u32, offer_id: u64) {
+pub fn owner_offers(env: &Env, owner: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<u64>>(&DataKey::OwnerOffers(owner.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn add_owner_offer(env: &Env, owner: &Address, offer_id: u64) {
+    let mut offers = owner_offers(env, owner);
+    offers.push_back(offer_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::OwnerOffers(owner.clone()), &offers);
+}
+
+pub fn remove_owner_offer(env: &Env, owner: &Address, offer_id: u64) {
+    let mut offers = owner_offers(env, owner);
+    if let Some(index) = offers.first_index_of(offer_id) {
+        offers.remove(index);
+    }
+    let key = DataKey::OwnerOffers(owner.clone());
+    if offers.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &offers);
+    }
+}
+
+pub fn bids(env: &Env, offer_id: u64) -> Vec<MixBid> {
     env.storage()
         .persistent()
-        .set(&DataKey::OwnerIndex(owner.clone(), recipe_id), &offer_id);
+        .get::<DataKey, Vec<MixBid>>(&DataKey::Bids(offer_id))
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_bids(env: &Env, offer_id: u64, bids: &Vec<MixBid>) {
+    if bids.is_empty() {
+        env.storage().persistent().remove(&DataKey::Bids(offer_id));
+    } else {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Bids(offer_id), bids);
+    }
 }
 
-pub fn clear_owner_index(env: &Env, owner: &Address, recipe_id: u32) {
+pub fn put_receipt(env: &Env, offer_id: u64, receipt: &MixReceipt) {
     env.storage()
         .persistent()
-        .remove(&DataKey::OwnerIndex(owner.clone(), recipe_id));
+        .set(&DataKey::Receipt(offer_id), receipt);
 }
 
-pub fn get_by_owner_recipe(env: &Env, owner: &Address, recipe_id: u32) -> Option<u64> {
+pub fn get_receipt(env: &Env, offer_id: u64) -> Result<MixReceipt, GameError> {
     env.storage()
         .persistent()
-        .get::<DataKey, u64>(&DataKey::OwnerIndex(owner.clone(), recipe_id))
+        .get::<DataKey, MixReceipt>(&DataKey::Receipt(offer_id))
+        .ok_or(GameError::OfferNotFound)
 }
 
 #[cfg(test)]
@@ -126,22 +257,27 @@ mod tests {
                 partner_fee_balls: 0,
                 partner_fee_stars: 0,
                 status: OfferStatus::WaitingForPartner,
+                mode: OfferMode::Instant,
+                accepted_at: None,
                 created_at: env.ledger().timestamp(),
                 deadline: env.ledger().timestamp() + 1000,
             };
 
-            set_owner_index(&env, &owner, offer.recipe_id, id1);
+            add_owner_offer(&env, &owner, id1);
+            add_owner_offer(&env, &owner, id2);
             put(&env, id1, &offer);
+            put(&env, id2, &offer);
 
-            assert_eq!(
-                get_by_owner_recipe(&env, &owner, offer.recipe_id),
-                Some(id1)
-            );
-            assert!(get_by_owner_recipe(&env, &other, offer.recipe_id).is_none());
+            assert_eq!(owner_offers(&env, &owner).len(), 2);
+            assert!(owner_offers(&env, &other).is_empty());
 
-            clear_owner_index(&env, &owner, offer.recipe_id);
+            remove_owner_offer(&env, &owner, id1);
             remove(&env, id1);
-            assert!(get_by_owner_recipe(&env, &owner, offer.recipe_id).is_none());
+            assert_eq!(owner_offers(&env, &owner).first(), Some(id2));
+
+            remove_owner_offer(&env, &owner, id2);
+            remove(&env, id2);
+            assert!(owner_offers(&env, &owner).is_empty());
         });
     }
 }
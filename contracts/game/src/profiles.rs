@@ -0,0 +1,252 @@
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+use crate::{errors::GameError, seasons};
+
+pub const LEADERBOARD_SIZE: u32 = 10;
+pub const XP_PER_PLAYER_LEVEL: u64 = 1_000;
+pub const MIN_NICKNAME_LEN: u32 = 3;
+pub const MAX_NICKNAME_LEN: u32 = 24;
+
+pub const MIX_XP: u64 = 50;
+pub const WIN_XP: u64 = 50;
+pub const TRADE_XP: u64 = 20;
+pub const UPGRADE_XP: u64 = 30;
+pub const CLAIM_XP: u64 = 10;
+
+/// Lifetime player state. The volumes sum marketplace prices on both sides of a trade,
+/// kept apart per payment token.
+#[derive(Clone)]
+#[contracttype]
+pub struct PlayerProfile {
+    pub nickname: Option<String>,
+    pub xp: u64,
+    pub level: u32,
+    pub mixes: u32,
+    pub wins: u32,
+    pub sales: u32,
+    pub teas_burned: u32,
+    pub volume_balls: i128,
+    pub volume_stars: i128,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[contracttype]
+pub enum LeaderboardMetric {
+    MixesWon,
+    VolumeBalls,
+    VolumeStars,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct LeaderboardEntry {
+    pub player: Address,
+    pub score: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Profile(Address),
+    Score(u32, LeaderboardMetric, Address),
+    Board(u32, LeaderboardMetric),
+}
+
+pub fn get(env: &Env, player: &Address) -> PlayerProfile {
+    env.storage()
+        .persistent()
+        .get::<DataKey, PlayerProfile>(&DataKey::Profile(player.clone()))
+        .unwrap_or(PlayerProfile {
+            nickname: None,
+            xp: 0,
+            level: 1,
+            mixes: 0,
+            wins: 0,
+            sales: 0,
+            teas_burned: 0,
+            volume_balls: 0,
+            volume_stars: 0,
+        })
+}
+
+fn put(env: &Env, player: &Address, profile: &PlayerProfile) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Profile(player.clone()), profile);
+}
+
+/// Applies `change` to a player's profile, keeping `level` in step with `xp`, and
+/// returns the updated profile.
+pub fn update(
+    env: &Env,
+    player: &Address,
+    xp: u64,
+    change: impl FnOnce(&mut PlayerProfile),
+) -> PlayerProfile {
+    let mut profile = get(env, player);
+    change(&mut profile);
+    profile.xp += xp;
+    profile.level = 1 + (profile.xp / XP_PER_PLAYER_LEVEL) as u32;
+    put(env, player, &profile);
+    profile
+}
+
+pub fn record_win(env: &Env, player: &Address) {
+    update(env, player, WIN_XP, |profile| profile.wins += 1);
+    add_score(env, player, LeaderboardMetric::MixesWon, 1);
+}
+
+pub fn record_burned(env: &Env, player: &Address, count: u32) {
+    update(env, player, 0, |profile| profile.teas_burned += count);
+}
+
+pub fn record_trade(env: &Env, player: &Address, balls: i128, stars: i128) {
+    update(env, player, 0, |profile| {
+        profile.volume_balls += balls;
+        profile.volume_stars += stars;
+    });
+    add_score(env, player, LeaderboardMetric::VolumeBalls, balls);
+    add_score(env, player, LeaderboardMetric::VolumeStars, stars);
+}
+
+pub fn set_nickname(env: &Env, player: &Address, nickname: String) -> Result<(), GameError> {
+    if nickname.len() < MIN_NICKNAME_LEN || nickname.len() > MAX_NICKNAME_LEN {
+        return Err(GameError::InvalidInput);
+    }
+    update(env, player, 0, |profile| profile.nickname = Some(nickname));
+    Ok(())
+}
+
+/// Season the leaderboards currently accumulate into: the running season, if any.
+/// Nothing is scored before the first season or between seasons.
+pub fn season(env: &Env) -> Option<u32> {
+    seasons::active(env).map(|(season_id, _)| season_id)
+}
+
+pub fn leaderboard(env: &Env, season: u32, metric: LeaderboardMetric) -> Vec<LeaderboardEntry> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<LeaderboardEntry>>(&DataKey::Board(season, metric))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Adds `delta` to the player's score for the running season and re-ranks them on
+/// the bounded leaderboard.
+pub fn add_score(env: &Env, player: &Address, metric: LeaderboardMetric, delta: i128) {
+    if delta <= 0 {
+        return;
+    }
+    let Some(season) = season(env) else {
+        return;
+    };
+    let score_key = DataKey::Score(season, metric, player.clone());
+    let score = env
+        .storage()
+        .persistent()
+        .get::<DataKey, i128>(&score_key)
+        .unwrap_or(0)
+        + delta;
+    env.storage().persistent().set(&score_key, &score);
+
+    let mut board = leaderboard(env, season, metric);
+    if let Some(index) = board.iter().position(|entry| entry.player == *player) {
+        board.remove(index as u32);
+    }
+    let position = board
+        .iter()
+        .position(|entry| entry.score < score)
+        .map(|index| index as u32)
+        .unwrap_or(board.len());
+    if position >= LEADERBOARD_SIZE {
+        return;
+    }
+    board.insert(
+        position,
+        LeaderboardEntry {
+            player: player.clone(),
+            score,
+        },
+    );
+    while board.len() > LEADERBOARD_SIZE {
+        board.pop_back();
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Board(season, metric), &board);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        Address, Env, Vec,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn leaderboard_stays_sorted_and_bounded() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            let players: std::vec::Vec<Address> = (0..LEADERBOARD_SIZE + 2)
+                .map(|_| Address::generate(&env))
+                .collect();
+            add_score(&env, &players[0], LeaderboardMetric::MixesWon, 1);
+            assert_eq!(season(&env), None);
+            assert!(leaderboard(&env, 0, LeaderboardMetric::MixesWon).is_empty());
+
+            seasons::start(
+                &env,
+                1,
+                &seasons::Season {
+                    starts_at: 0,
+                    ends_at: 100,
+                    premium_price_stars: 0,
+                    tiers: Vec::new(&env),
+                },
+            )
+            .unwrap();
+            assert_eq!(season(&env), Some(1));
+            for (rank, player) in players.iter().enumerate() {
+                add_score(&env, player, LeaderboardMetric::MixesWon, rank as i128 + 1);
+            }
+            let board = leaderboard(&env, 1, LeaderboardMetric::MixesWon);
+            assert_eq!(board.len(), LEADERBOARD_SIZE);
+            assert_eq!(board.get(0).unwrap().score, LEADERBOARD_SIZE as i128 + 2);
+
+            add_score(&env, &players[0], LeaderboardMetric::MixesWon, 100);
+            let board = leaderboard(&env, 1, LeaderboardMetric::MixesWon);
+            assert_eq!(board.len(), LEADERBOARD_SIZE);
+            assert_eq!(board.get(0).unwrap().player, players[0]);
+            assert_eq!(board.get(0).unwrap().score, 101);
+
+            let profile = update(&env, &players[0], 2_500, |profile| profile.mixes += 1);
+            assert_eq!((profile.mixes, profile.level), (1, 3));
+
+            record_trade(&env, &players[1], 0, 40);
+            let profile = get(&env, &players[1]);
+            assert_eq!((profile.volume_balls, profile.volume_stars), (0, 40));
+            assert!(leaderboard(&env, 1, LeaderboardMetric::VolumeBalls).is_empty());
+            assert_eq!(
+                leaderboard(&env, 1, LeaderboardMetric::VolumeStars).len(),
+                1
+            );
+
+            env.ledger().set_timestamp(101);
+            assert_eq!(season(&env), None);
+            record_trade(&env, &players[2], 0, 40);
+            assert_eq!(
+                leaderboard(&env, 1, LeaderboardMetric::VolumeStars).len(),
+                1
+            );
+        });
+    }
+}
@@ -0,0 +1,236 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::GameError;
+
+/// Quests tracked at once; expired quests are pruned when new ones are defined.
+pub const MAX_ACTIVE_QUESTS: u32 = 16;
+
+/// What a quest counts. `Mix` optionally narrows to one recipe; `ReachLevel`
+/// completes as soon as any upgrade brings a tea to that level.
+#[derive(Clone, PartialEq, Eq)]
+#[contracttype]
+pub enum QuestGoal {
+    Mix(Option<u32>),
+    Sell,
+    Purchase,
+    ReachLevel(u32),
+    Claim,
+}
+
+/// A player action reported by the game entrypoints.
+#[derive(Clone)]
+#[contracttype]
+pub enum QuestAction {
+    Mix(u32),
+    Sell,
+    Purchase,
+    Upgrade(u32),
+    Claim,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Quest {
+    pub goal: QuestGoal,
+    pub target: u32,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub reward_balls: i128,
+    pub reward_stars: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct QuestProgress {
+    pub count: u32,
+    pub claimed: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Quest(u32),
+    QuestIds,
+    Progress(Address, u32),
+}
+
+pub fn get(env: &Env, quest_id: u32) -> Result<Quest, GameError> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Quest>(&DataKey::Quest(quest_id))
+        .ok_or(GameError::OfferNotFound)
+}
+
+pub fn ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Vec<u32>>(&DataKey::QuestIds)
+        .unwrap_or(Vec::new(env))
+}
+
+fn is_open(env: &Env, quest: &Quest) -> bool {
+    let now = env.ledger().timestamp();
+    quest.starts_at <= now && now <= quest.ends_at
+}
+
+/// Stores a quest. A quest can be replaced until it starts; after that its id is
+/// taken for good, since players' progress is kept under it.
+pub fn define(env: &Env, quest_id: u32, quest: &Quest) -> Result<(), GameError> {
+    let now = env.ledger().timestamp();
+    if get(env, quest_id).is_ok_and(|existing| existing.starts_at <= now) {
+        return Err(GameError::InvalidInput);
+    }
+    let mut tracked = Vec::new(env);
+    for id in ids(env).iter() {
+        if id == quest_id {
+            continue;
+        }
+        if get(env, id).is_ok_and(|existing| existing.ends_at >= now) {
+            tracked.push_back(id);
+        }
+    }
+    if tracked.len() >= MAX_ACTIVE_QUESTS {
+        return Err(GameError::LimitExceeded);
+    }
+    tracked.push_back(quest_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Quest(quest_id), quest);
+    env.storage().instance().set(&DataKey::QuestIds, &tracked);
+    Ok(())
+}
+
+pub fn progress(env: &Env, player: &Address, quest_id: u32) -> QuestProgress {
+    env.storage()
+        .persistent()
+        .get::<DataKey, QuestProgress>(&DataKey::Progress(player.clone(), quest_id))
+        .unwrap_or(QuestProgress {
+            count: 0,
+            claimed: false,
+        })
+}
+
+fn put_progress(env: &Env, player: &Address, quest_id: u32, progress: &QuestProgress) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Progress(player.clone(), quest_id), progress);
+}
+
+/// How far `action` moves a quest: a count for tallying goals, the full target for
+/// a reached level, or nothing when it does not apply.
+fn advance(quest: &Quest, action: &QuestAction) -> u32 {
+    match (&quest.goal, action) {
+        (QuestGoal::Mix(None), QuestAction::Mix(_)) => 1,
+        (QuestGoal::Mix(Some(wanted)), QuestAction::Mix(recipe_id)) if wanted == recipe_id => 1,
+        (QuestGoal::Sell, QuestAction::Sell)
+        | (QuestGoal::Purchase, QuestAction::Purchase)
+        | (QuestGoal::Claim, QuestAction::Claim) => 1,
+        (QuestGoal::ReachLevel(level), QuestAction::Upgrade(reached)) if reached >= level => {
+            quest.target
+        }
+        _ => 0,
+    }
+}
+
+/// Credits `action` to every open quest it advances.
+pub fn record(env: &Env, player: &Address, action: QuestAction) {
+    for quest_id in ids(env).iter() {
+        let Ok(quest) = get(env, quest_id) else {
+            continue;
+        };
+        if !is_open(env, &quest) {
+            continue;
+        }
+        let step = advance(&quest, &action);
+        if step == 0 {
+            continue;
+        }
+        let mut progress = progress(env, player, quest_id);
+        if progress.claimed || progress.count >= quest.target {
+            continue;
+        }
+        progress.count = (progress.count + step).min(quest.target);
+        put_progress(env, player, quest_id, &progress);
+    }
+}
+
+/// Marks a completed quest as claimed and returns it so the caller can pay out.
+pub fn claim(env: &Env, player: &Address, quest_id: u32) -> Result<Quest, GameError> {
+    let quest = get(env, quest_id)?;
+    let mut progress = progress(env, player, quest_id);
+    if progress.claimed {
+        return Err(GameError::AlreadyClaimed);
+    }
+    if progress.count < quest.target {
+        return Err(GameError::NotReady);
+    }
+    progress.claimed = true;
+    put_progress(env, player, quest_id, &progress);
+    Ok(quest)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        Address, Env,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    fn quest(goal: QuestGoal, target: u32) -> Quest {
+        Quest {
+            goal,
+            target,
+            starts_at: 10,
+            ends_at: 100,
+            reward_balls: 1_000,
+            reward_stars: 0,
+        }
+    }
+
+    #[test]
+    fn progress_counts_matching_actions_within_window() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            define(&env, 1, &quest(QuestGoal::Mix(Some(7)), 2)).unwrap();
+            define(&env, 2, &quest(QuestGoal::Sell, 1)).unwrap();
+            define(&env, 2, &quest(QuestGoal::ReachLevel(5), 1)).unwrap();
+
+            record(&env, &player, QuestAction::Mix(7));
+            assert_eq!(progress(&env, &player, 1).count, 0);
+
+            env.ledger().set_timestamp(50);
+            record(&env, &player, QuestAction::Mix(7));
+            record(&env, &player, QuestAction::Mix(3));
+            record(&env, &player, QuestAction::Upgrade(4));
+            assert_eq!(claim(&env, &player, 1).err(), Some(GameError::NotReady));
+
+            record(&env, &player, QuestAction::Mix(7));
+            record(&env, &player, QuestAction::Upgrade(5));
+            assert_eq!(claim(&env, &player, 1).unwrap().reward_balls, 1_000);
+            assert_eq!(
+                claim(&env, &player, 1).err(),
+                Some(GameError::AlreadyClaimed)
+            );
+            assert!(claim(&env, &player, 2).is_ok());
+            assert_eq!(
+                define(&env, 1, &quest(QuestGoal::Sell, 1)),
+                Err(GameError::InvalidInput)
+            );
+
+            env.ledger().set_timestamp(101);
+            define(&env, 3, &quest(QuestGoal::Sell, 1)).unwrap();
+            assert_eq!(ids(&env).len(), 1);
+        });
+    }
+}
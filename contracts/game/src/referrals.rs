@@ -0,0 +1,135 @@
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::errors::GameError;
+
+pub const DEFAULT_BONUS_BPS: u32 = 500;
+/// Highest configurable bonus. Trade and mix bonuses are a share of what the game keeps
+/// (the market fee or the treasury share of mix fees), so even at the cap a wash trade
+/// or wash mix costs more than its bonus.
+pub const MAX_BONUS_BPS: u32 = 5_000;
+/// Longest referrer chain walked when checking a registration for cycles.
+pub const MAX_CHAIN_DEPTH: u32 = 64;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ReferralStats {
+    pub referees: u32,
+    pub earned_balls: i128,
+    pub earned_stars: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Referrer(Address),
+    Stats(Address),
+    BonusBps,
+}
+
+pub fn referrer(env: &Env, player: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Address>(&DataKey::Referrer(player.clone()))
+}
+
+pub fn stats(env: &Env, referrer: &Address) -> ReferralStats {
+    env.storage()
+        .persistent()
+        .get::<DataKey, ReferralStats>(&DataKey::Stats(referrer.clone()))
+        .unwrap_or(ReferralStats {
+            referees: 0,
+            earned_balls: 0,
+            earned_stars: 0,
+        })
+}
+
+fn put_stats(env: &Env, referrer: &Address, stats: &ReferralStats) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Stats(referrer.clone()), stats);
+}
+
+pub fn set_bonus_bps(env: &Env, bps: u32) {
+    env.storage().instance().set(&DataKey::BonusBps, &bps);
+}
+
+pub fn bonus_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get::<DataKey, u32>(&DataKey::BonusBps)
+        .unwrap_or(DEFAULT_BONUS_BPS)
+}
+
+/// Links `player` to `referrer`. The caller checks that the player is new. A player
+/// registers once; self-referral and links that would close a loop in the referrer
+/// chain are rejected.
+pub fn register(env: &Env, player: &Address, referrer: &Address) -> Result<(), GameError> {
+    if self::referrer(env, player).is_some() {
+        return Err(GameError::AlreadyClaimed);
+    }
+    let mut current = referrer.clone();
+    for _ in 0..MAX_CHAIN_DEPTH {
+        if current == *player {
+            return Err(GameError::InvalidInput);
+        }
+        match self::referrer(env, &current) {
+            Some(next) => current = next,
+            None => {
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::Referrer(player.clone()), referrer);
+                let mut stats = stats(env, referrer);
+                stats.referees += 1;
+                put_stats(env, referrer, &stats);
+                return Ok(());
+            }
+        }
+    }
+    Err(GameError::LimitExceeded)
+}
+
+/// Referrer's bonus on an amount a referee paid or earned.
+pub fn bonus(env: &Env, amount: i128) -> i128 {
+    amount.max(0) * bonus_bps(env) as i128 / 10_000
+}
+
+pub fn record_reward(env: &Env, referrer: &Address, balls: i128, stars: i128) {
+    let mut stats = stats(env, referrer);
+    stats.earned_balls += balls;
+    stats.earned_stars += stars;
+    put_stats(env, referrer, &stats);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Address as _, Address, Env};
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn registration_rejects_self_and_cycles() {
+        let env = Env::default();
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let carol = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            assert_eq!(register(&env, &alice, &alice), Err(GameError::InvalidInput));
+
+            register(&env, &bob, &alice).unwrap();
+            register(&env, &carol, &bob).unwrap();
+            assert_eq!(register(&env, &bob, &carol), Err(GameError::AlreadyClaimed));
+            assert_eq!(register(&env, &alice, &carol), Err(GameError::InvalidInput));
+
+            assert_eq!(referrer(&env, &carol), Some(bob.clone()));
+            assert_eq!(stats(&env, &alice).referees, 1);
+            assert_eq!(bonus(&env, 10_000), 500);
+        });
+    }
+}
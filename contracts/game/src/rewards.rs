@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Env};
+use soroban_sdk::{contracttype, Address, Env, Vec};
 
 use crate::errors::GameError;
 
@@ -6,27 +6,158 @@ use crate::errors::GameError;
 #[contracttype]
 pub enum DataKey {
     LastClaim(Address),
+    Streak(Address),
+    StreakConfig,
 }
 
 pub const CLAIM_INTERVAL: u64 = 86_400;
+pub const MAX_MULTIPLIERS: u32 = 30;
+const BASE_MULTIPLIER: u32 = 10_000;
 
-pub fn ensure_claimable(env: &Env, player: &Address) -> Result<(), GameError> {
-    let now = env.ledger().timestamp();
-    if let Some(last) = env
-        .storage()
+/// Claim cadence. A claim is allowed `interval` seconds after the previous one and
+/// keeps the streak alive until `grace` seconds after that. `multipliers` are in basis
+/// points, indexed by streak length starting at one; the last entry applies to longer
+/// streaks.
+#[derive(Clone)]
+#[contracttype]
+pub struct StreakConfig {
+    pub interval: u64,
+    pub grace: u64,
+    pub multipliers: Vec<u32>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct StreakStatus {
+    pub count: u32,
+    pub last_claim: Option<u64>,
+    pub multiplier_bps: u32,
+}
+
+pub fn set_config(env: &Env, config: &StreakConfig) {
+    env.storage().instance().set(&DataKey::StreakConfig, config);
+}
+
+pub fn config(env: &Env) -> StreakConfig {
+    env.storage()
+        .instance()
+        .get::<DataKey, StreakConfig>(&DataKey::StreakConfig)
+        .unwrap_or(StreakConfig {
+            interval: CLAIM_INTERVAL,
+            grace: CLAIM_INTERVAL,
+            multipliers: Vec::new(env),
+        })
+}
+
+fn last_claim(env: &Env, player: &Address) -> Option<u64> {
+    env.storage()
         .persistent()
         .get::<DataKey, u64>(&DataKey::LastClaim(player.clone()))
-    {
-        if now.saturating_sub(last) < CLAIM_INTERVAL {
+}
+
+fn stored_streak(env: &Env, player: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::Streak(player.clone()))
+        .unwrap_or(0)
+}
+
+pub fn multiplier(config: &StreakConfig, streak: u32) -> u32 {
+    if streak == 0 || config.multipliers.is_empty() {
+        return BASE_MULTIPLIER;
+    }
+    let index = (streak - 1).min(config.multipliers.len() - 1);
+    config.multipliers.get_unchecked(index)
+}
+
+/// The player's streak as of now; a streak whose window has lapsed reads as zero.
+pub fn streak(env: &Env, player: &Address) -> StreakStatus {
+    let config = config(env);
+    let last = last_claim(env, player);
+    let alive = last.is_some_and(|last| {
+        env.ledger().timestamp().saturating_sub(last) <= config.interval + config.grace
+    });
+    let count = if alive { stored_streak(env, player) } else { 0 };
+    StreakStatus {
+        count,
+        last_claim: last,
+        multiplier_bps: multiplier(&config, count),
+    }
+}
+
+pub fn ensure_claimable(env: &Env, player: &Address) -> Result<(), GameError> {
+    let now = env.ledger().timestamp();
+    if let Some(last) = last_claim(env, player) {
+        if now.saturating_sub(last) < config(env).interval {
             return Err(GameError::AlreadyClaimed);
         }
     }
     Ok(())
 }
 
-pub fn record_claim(env: &Env, player: &Address) {
+/// Records a claim and returns the reward multiplier it earns, in basis points.
+pub fn record_claim(env: &Env, player: &Address) -> u32 {
+    let count = streak(env, player).count + 1;
     let now = env.ledger().timestamp();
     env.storage()
         .persistent()
         .set(&DataKey::LastClaim(player.clone()), &now);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Streak(player.clone()), &count);
+    multiplier(&config(env), count)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        vec, Address, Env,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn streak_grows_within_grace_and_resets_after() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            set_config(
+                &env,
+                &StreakConfig {
+                    interval: 100,
+                    grace: 50,
+                    multipliers: vec![&env, 10_000, 12_000, 15_000],
+                },
+            );
+
+            env.ledger().set_timestamp(1_000);
+            assert_eq!(record_claim(&env, &player), 10_000);
+            env.ledger().set_timestamp(1_050);
+            assert_eq!(
+                ensure_claimable(&env, &player),
+                Err(GameError::AlreadyClaimed)
+            );
+
+            env.ledger().set_timestamp(1_150);
+            assert_eq!(record_claim(&env, &player), 12_000);
+            env.ledger().set_timestamp(1_250);
+            assert_eq!(record_claim(&env, &player), 15_000);
+            env.ledger().set_timestamp(1_350);
+            assert_eq!(record_claim(&env, &player), 15_000);
+            assert_eq!(streak(&env, &player).count, 4);
+
+            env.ledger().set_timestamp(1_501);
+            assert_eq!(streak(&env, &player).count, 0);
+            assert_eq!(record_claim(&env, &player), 10_000);
+        });
+    }
 }
@@ -0,0 +1,164 @@
+use soroban_sdk::{contracttype, Env};
+
+use crate::{errors::GameError, tea::TeaMetadata};
+
+/// BALLS paid per point of rarity.
+pub const RARITY_VALUE: i128 = 500_000;
+/// BALLS paid per level.
+pub const LEVEL_VALUE: i128 = 250_000;
+/// Salvage never returns more than this share of the BALLS actually paid into a tea.
+pub const MAX_REFUND_BPS: i128 = 5_000;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct SalvageSpend {
+    pub day: u64,
+    pub spent: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Budget,
+    Spend,
+    Invested(u64),
+}
+
+fn current_day(env: &Env) -> u64 {
+    env.ledger().timestamp() / 86_400
+}
+
+/// Payout for a tea: its rarity and level, capped at a share of what was paid into it
+/// (`invested`), so cheap upgrades and free levels cannot be cashed out.
+pub fn value(metadata: &TeaMetadata, invested: i128) -> i128 {
+    let worth = metadata.rarity as i128 * RARITY_VALUE + metadata.level as i128 * LEVEL_VALUE;
+    worth.min(invested.max(0) * MAX_REFUND_BPS / 10_000)
+}
+
+/// BALLS a tea's owners paid into it and did not get back: its crafting charge and
+/// upgrades. Teas from fee-only mixes, rewards or outside the game have none.
+pub fn invested(env: &Env, token_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, i128>(&DataKey::Invested(token_id))
+        .unwrap_or(0)
+}
+
+pub fn record_investment(env: &Env, token_id: u64, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+    let total = invested(env, token_id) + amount;
+    env.storage()
+        .persistent()
+        .set(&DataKey::Invested(token_id), &total);
+}
+
+pub fn clear(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Invested(token_id));
+}
+
+pub fn set_budget(env: &Env, amount: i128) {
+    env.storage().instance().set(&DataKey::Budget, &amount);
+}
+
+/// Daily salvage budget. Salvage pays nothing until the admin sets one.
+pub fn budget(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get::<DataKey, i128>(&DataKey::Budget)
+        .unwrap_or(0)
+}
+
+fn spent_today(env: &Env) -> i128 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, SalvageSpend>(&DataKey::Spend)
+        .filter(|spend| spend.day == current_day(env))
+        .map(|spend| spend.spent)
+        .unwrap_or(0)
+}
+
+pub fn remaining(env: &Env) -> i128 {
+    (budget(env) - spent_today(env)).max(0)
+}
+
+/// Reserves `amount` from today's budget. Payouts are never cut short: a salvage that
+/// does not fit the remaining budget is refused so the tea is kept.
+pub fn consume(env: &Env, amount: i128) -> Result<(), GameError> {
+    if amount > remaining(env) {
+        return Err(GameError::LimitExceeded);
+    }
+    let spend = SalvageSpend {
+        day: current_day(env),
+        spent: spent_today(env) + amount,
+    };
+    env.storage().persistent().set(&DataKey::Spend, &spend);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Ledger, Env, String, Vec};
+
+    use super::*;
+    use crate::tea::TeaStats;
+
+    #[contract]
+    struct Dummy;
+
+    #[test]
+    fn budget_resets_daily() {
+        let env = Env::default();
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            assert_eq!(consume(&env, 1), Err(GameError::LimitExceeded));
+
+            set_budget(&env, 1_000);
+            consume(&env, 600).unwrap();
+            assert_eq!(remaining(&env), 400);
+            assert_eq!(consume(&env, 500), Err(GameError::LimitExceeded));
+            assert_eq!(remaining(&env), 400);
+
+            env.ledger().set_timestamp(86_400);
+            assert_eq!(remaining(&env), 1_000);
+            consume(&env, 1_000).unwrap();
+            assert_eq!(remaining(&env), 0);
+        });
+    }
+
+    #[test]
+    fn value_is_capped_by_spend() {
+        let env = Env::default();
+        let metadata = TeaMetadata {
+            display_name: String::from_str(&env, "Upgraded"),
+            flavor_profile: String::from_str(&env, "earthy"),
+            rarity: 3,
+            level: 10,
+            infusion: String::from_str(&env, "base"),
+            stats: TeaStats {
+                sweetness: 1,
+                body: 1,
+                caffeine: 1,
+            },
+            lineage: Vec::new(&env),
+            image_uri: String::from_str(&env, "ipfs://upgraded"),
+        };
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            record_investment(&env, 1, 10);
+            record_investment(&env, 1, -10);
+            assert_eq!(value(&metadata, invested(&env, 1)), 5);
+            assert_eq!(value(&metadata, 100_000_000), 4_000_000);
+
+            clear(&env, 1);
+            assert_eq!(invested(&env, 1), 0);
+        });
+    }
+}
@@ -0,0 +1,220 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::errors::GameError;
+
+pub const MAX_TIERS: u32 = 50;
+
+pub const MIX_POINTS: u32 = 10;
+pub const UPGRADE_POINTS: u32 = 5;
+pub const EVENT_POINTS: u32 = 5;
+pub const CLAIM_POINTS: u32 = 2;
+
+/// One step of a season's reward track. Premium tiers are only claimable by players
+/// who bought the premium pass; `recipe_id` mints a tea from that recipe.
+#[derive(Clone)]
+#[contracttype]
+pub struct SeasonTier {
+    pub points: u32,
+    pub premium: bool,
+    pub reward_balls: i128,
+    pub reward_stars: i128,
+    pub recipe_id: Option<u32>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Season {
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub premium_price_stars: i128,
+    pub tiers: Vec<SeasonTier>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Current,
+    Season(u32),
+    Points(u32, Address),
+    Premium(u32, Address),
+    Claimed(u32, Address, u32),
+}
+
+pub fn current_id(env: &Env) -> Option<u32> {
+    env.storage()
+        .instance()
+        .get::<DataKey, u32>(&DataKey::Current)
+}
+
+pub fn get(env: &Env, season_id: u32) -> Result<Season, GameError> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Season>(&DataKey::Season(season_id))
+        .ok_or(GameError::OfferNotFound)
+}
+
+fn is_running(env: &Env, season: &Season) -> bool {
+    let now = env.ledger().timestamp();
+    season.starts_at <= now && now <= season.ends_at
+}
+
+/// The current season while it is running.
+pub fn active(env: &Env) -> Option<(u32, Season)> {
+    let season_id = current_id(env)?;
+    let season = get(env, season_id).ok()?;
+    is_running(env, &season).then_some((season_id, season))
+}
+
+/// Opens a new season. Ids only move forward and the previous season must be over;
+/// its points stay archived under its id.
+pub fn start(env: &Env, season_id: u32, season: &Season) -> Result<(), GameError> {
+    if let Some(current) = current_id(env) {
+        if season_id <= current {
+            return Err(GameError::InvalidInput);
+        }
+        if get(env, current).is_ok_and(|previous| env.ledger().timestamp() <= previous.ends_at) {
+            return Err(GameError::NotReady);
+        }
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Season(season_id), season);
+    env.storage().instance().set(&DataKey::Current, &season_id);
+    Ok(())
+}
+
+pub fn points(env: &Env, season_id: u32, player: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::Points(season_id, player.clone()))
+        .unwrap_or(0)
+}
+
+/// Credits points to the running season; outside a season this does nothing.
+pub fn add_points(env: &Env, player: &Address, amount: u32) {
+    let Some((season_id, _)) = active(env) else {
+        return;
+    };
+    let total = points(env, season_id, player) + amount;
+    env.storage()
+        .persistent()
+        .set(&DataKey::Points(season_id, player.clone()), &total);
+}
+
+pub fn has_premium(env: &Env, season_id: u32, player: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Premium(season_id, player.clone()))
+}
+
+pub fn grant_premium(env: &Env, season_id: u32, player: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Premium(season_id, player.clone()), &true);
+}
+
+pub fn is_claimed(env: &Env, season_id: u32, player: &Address, tier: u32) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Claimed(season_id, player.clone(), tier))
+}
+
+/// Marks a tier as claimed once the player has the points (and pass) for it, and
+/// returns the tier so the caller can pay out.
+pub fn claim_tier(
+    env: &Env,
+    season_id: u32,
+    player: &Address,
+    tier: u32,
+) -> Result<SeasonTier, GameError> {
+    let season = get(env, season_id)?;
+    let reward = season.tiers.get(tier).ok_or(GameError::InvalidInput)?;
+    if is_claimed(env, season_id, player, tier) {
+        return Err(GameError::AlreadyClaimed);
+    }
+    if reward.premium && !has_premium(env, season_id, player) {
+        return Err(GameError::Unauthorized);
+    }
+    if points(env, season_id, player) < reward.points {
+        return Err(GameError::NotReady);
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::Claimed(season_id, player.clone(), tier), &true);
+    Ok(reward)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        vec, Address, Env,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    fn season(env: &Env, starts_at: u64, ends_at: u64) -> Season {
+        Season {
+            starts_at,
+            ends_at,
+            premium_price_stars: 100,
+            tiers: vec![
+                env,
+                SeasonTier {
+                    points: 10,
+                    premium: false,
+                    reward_balls: 1_000,
+                    reward_stars: 0,
+                    recipe_id: None,
+                },
+                SeasonTier {
+                    points: 10,
+                    premium: true,
+                    reward_balls: 0,
+                    reward_stars: 50,
+                    recipe_id: Some(3),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn points_archive_per_season() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            start(&env, 1, &season(&env, 0, 100)).unwrap();
+            add_points(&env, &player, MIX_POINTS);
+            assert_eq!(
+                claim_tier(&env, 1, &player, 1).err(),
+                Some(GameError::Unauthorized)
+            );
+            assert_eq!(claim_tier(&env, 1, &player, 0).unwrap().reward_balls, 1_000);
+            assert_eq!(
+                claim_tier(&env, 1, &player, 0).err(),
+                Some(GameError::AlreadyClaimed)
+            );
+
+            assert_eq!(
+                start(&env, 2, &season(&env, 50, 200)),
+                Err(GameError::NotReady)
+            );
+            env.ledger().set_timestamp(101);
+            add_points(&env, &player, MIX_POINTS);
+            assert_eq!(points(&env, 1, &player), MIX_POINTS);
+
+            start(&env, 2, &season(&env, 101, 200)).unwrap();
+            assert_eq!(points(&env, 2, &player), 0);
+            grant_premium(&env, 1, &player);
+            assert!(claim_tier(&env, 1, &player, 1).is_ok());
+        });
+    }
+}
@@ -0,0 +1,197 @@
+use soroban_sdk::{contracttype, Address, Env, String, Vec};
+
+use crate::{errors::GameError, tea::TeaMetadata};
+
+pub const MIN_SLOTS: u32 = 3;
+pub const MAX_SLOTS: u32 = 8;
+
+#[derive(Clone)]
+#[contracttype]
+pub struct IngredientSlot {
+    pub flavor_profile: Option<String>,
+    pub min_rarity: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Contribution {
+    pub contributor: Address,
+    pub slot: u32,
+    pub token_id: u64,
+    pub fee_balls: i128,
+    pub fee_stars: i128,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+#[contracttype]
+pub enum SessionStatus {
+    Collecting,
+    Completed,
+    Cancelled,
+}
+
+/// A multi-ingredient mix being collected. `slots` is the recipe's slot list as it was
+/// when the session opened, so later recipe edits cannot strand the session.
+#[derive(Clone)]
+#[contracttype]
+pub struct MixSession {
+    pub creator: Address,
+    pub recipe_id: u32,
+    pub slots: Vec<IngredientSlot>,
+    pub contributions: Vec<Contribution>,
+    pub status: SessionStatus,
+    pub created_at: u64,
+    pub deadline: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    RecipeSlots(u32),
+    Session(u64),
+    Sequence,
+}
+
+pub fn set_slots(env: &Env, recipe_id: u32, slots: &Vec<IngredientSlot>) {
+    if slots.is_empty() {
+        env.storage()
+            .instance()
+            .remove(&DataKey::RecipeSlots(recipe_id));
+    } else {
+        env.storage()
+            .instance()
+            .set(&DataKey::RecipeSlots(recipe_id), slots);
+    }
+}
+
+pub fn slots(env: &Env, recipe_id: u32) -> Vec<IngredientSlot> {
+    env.storage()
+        .instance()
+        .get::<DataKey, Vec<IngredientSlot>>(&DataKey::RecipeSlots(recipe_id))
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn next_id(env: &Env) -> u64 {
+    let mut seq = env
+        .storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::Sequence)
+        .unwrap_or(0);
+    seq += 1;
+    env.storage().persistent().set(&DataKey::Sequence, &seq);
+    seq
+}
+
+pub fn put(env: &Env, id: u64, session: &MixSession) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Session(id), session);
+}
+
+pub fn get(env: &Env, id: u64) -> Result<MixSession, GameError> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, MixSession>(&DataKey::Session(id))
+        .ok_or(GameError::OfferNotFound)
+}
+
+pub fn slot_taken(session: &MixSession, slot: u32) -> bool {
+    session
+        .contributions
+        .iter()
+        .any(|contribution| contribution.slot == slot)
+}
+
+pub fn ensure_fits(slot: &IngredientSlot, metadata: &TeaMetadata) -> Result<(), GameError> {
+    if metadata.rarity < slot.min_rarity {
+        return Err(GameError::InvalidInput);
+    }
+    if let Some(profile) = &slot.flavor_profile {
+        if *profile != metadata.flavor_profile {
+            return Err(GameError::InvalidInput);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use soroban_sdk::{contract, testutils::Address as _, Address, Env, String, Vec};
+
+    use super::*;
+    use crate::tea::TeaStats;
+
+    #[contract]
+    struct Dummy;
+
+    fn sample_tea(env: &Env, profile: &str, rarity: u32) -> TeaMetadata {
+        TeaMetadata {
+            display_name: String::from_str(env, "Blend"),
+            flavor_profile: String::from_str(env, profile),
+            rarity,
+            level: 1,
+            infusion: String::from_str(env, "base"),
+            stats: TeaStats {
+                sweetness: 1,
+                body: 1,
+                caffeine: 1,
+            },
+            lineage: Vec::new(env),
+            image_uri: String::from_str(env, "ipfs://blend"),
+        }
+    }
+
+    #[test]
+    fn slots_filter_ingredients() {
+        let env = Env::default();
+        let contributor = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            let citrus = IngredientSlot {
+                flavor_profile: Some(String::from_str(&env, "citrus")),
+                min_rarity: 2,
+            };
+            let any = IngredientSlot {
+                flavor_profile: None,
+                min_rarity: 0,
+            };
+            let mut recipe_slots = Vec::new(&env);
+            recipe_slots.push_back(citrus.clone());
+            recipe_slots.push_back(any.clone());
+            recipe_slots.push_back(any.clone());
+            set_slots(&env, 9, &recipe_slots);
+            assert_eq!(slots(&env, 9).len(), 3);
+
+            assert!(ensure_fits(&citrus, &sample_tea(&env, "citrus", 2)).is_ok());
+            assert!(ensure_fits(&citrus, &sample_tea(&env, "citrus", 1)).is_err());
+            assert!(ensure_fits(&citrus, &sample_tea(&env, "floral", 3)).is_err());
+            assert!(ensure_fits(&any, &sample_tea(&env, "floral", 0)).is_ok());
+
+            let mut contributions = Vec::new(&env);
+            contributions.push_back(Contribution {
+                contributor: contributor.clone(),
+                slot: 1,
+                token_id: 4,
+                fee_balls: 0,
+                fee_stars: 0,
+            });
+            let session = MixSession {
+                creator: contributor.clone(),
+                recipe_id: 9,
+                slots: recipe_slots.clone(),
+                contributions,
+                status: SessionStatus::Collecting,
+                created_at: 0,
+                deadline: 100,
+            };
+            assert!(slot_taken(&session, 1));
+            assert!(!slot_taken(&session, 0));
+
+            set_slots(&env, 9, &Vec::new(&env));
+            assert!(slots(&env, 9).is_empty());
+        });
+    }
+}
@@ -51,6 +51,20 @@ pub fn mint_tea(env: &Env, tea_contract: &Address, to: &Address, metadata: TeaMe
     env.invoke_contract::<u64>(&tea_contract, &symbol(env, "mint"), call.into_val(env))
 }
 
+pub fn mint_soulbound(
+    env: &Env,
+    tea_contract: &Address,
+    to: &Address,
+    metadata: TeaMetadata,
+) -> u64 {
+    let call = (env.current_contract_address(), to.clone(), metadata);
+    env.invoke_contract::<u64>(
+        tea_contract,
+        &symbol(env, "mint_soulbound"),
+        call.into_val(env),
+    )
+}
+
 pub fn update_tea_level(env: &Env, tea_contract: &Address, token_id: u64, level: u32) {
     let call = (env.current_contract_address(), token_id, level);
     let _ = env.invoke_contract::<()>(&tea_contract, &symbol(env, "set_level"), call.into_val(env));
@@ -88,6 +102,20 @@ pub fn get_tea_metadata(env: &Env, tea_contract: &Address, token_id: u64) -> Tea
     )
 }
 
+pub fn is_soulbound(env: &Env, tea_contract: &Address, token_id: u64) -> bool {
+    let call = (token_id,);
+    env.invoke_contract::<bool>(
+        tea_contract,
+        &symbol(env, "is_soulbound"),
+        call.into_val(env),
+    )
+}
+
+pub fn tea_minted_at(env: &Env, tea_contract: &Address, token_id: u64) -> Option<u64> {
+    let call = (token_id,);
+    env.invoke_contract::<Option<u64>>(tea_contract, &symbol(env, "minted_at"), call.into_val(env))
+}
+
 pub fn owner_of(env: &Env, tea_contract: &Address, token_id: u64) -> Address {
     let call = (token_id,);
     env.invoke_contract::<Address>(&tea_contract, &symbol(env, "owner"), call.into_val(env))
@@ -0,0 +1,170 @@
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+use crate::errors::GameError;
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Signer,
+    Used(Address, u64),
+}
+
+pub fn set_signer(env: &Env, key: &BytesN<32>) {
+    env.storage().instance().set(&DataKey::Signer, key);
+}
+
+pub fn signer(env: &Env) -> Option<BytesN<32>> {
+    env.storage()
+        .instance()
+        .get::<DataKey, BytesN<32>>(&DataKey::Signer)
+}
+
+pub fn is_used(env: &Env, player: &Address, nonce: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Used(player.clone(), nonce))
+}
+
+/// Bytes the server signs: `xdr((game, player, amount_balls, amount_stars, nonce,
+/// expiry))`. Binding the game address keeps vouchers from replaying across
+/// deployments.
+pub fn message(
+    env: &Env,
+    player: &Address,
+    amount_balls: i128,
+    amount_stars: i128,
+    nonce: u64,
+    expiry: u64,
+) -> Bytes {
+    (
+        env.current_contract_address(),
+        player.clone(),
+        amount_balls,
+        amount_stars,
+        nonce,
+        expiry,
+    )
+        .to_xdr(env)
+}
+
+/// Checks a voucher and burns its nonce. An invalid signature traps inside
+/// `ed25519_verify`.
+pub fn redeem(
+    env: &Env,
+    player: &Address,
+    amount_balls: i128,
+    amount_stars: i128,
+    nonce: u64,
+    expiry: u64,
+    signature: &BytesN<64>,
+) -> Result<(), GameError> {
+    let key = signer(env).ok_or(GameError::Unauthorized)?;
+    if env.ledger().timestamp() > expiry {
+        return Err(GameError::Expired);
+    }
+    if is_used(env, player, nonce) {
+        return Err(GameError::AlreadyClaimed);
+    }
+    let message = message(env, player, amount_balls, amount_stars, nonce, expiry);
+    env.crypto().ed25519_verify(&key, &message, signature);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Used(player.clone(), nonce), &true);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+        Address, Bytes, BytesN, Env,
+    };
+
+    use super::*;
+
+    #[contract]
+    struct Dummy;
+
+    fn register_signer(env: &Env) -> SigningKey {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        set_signer(
+            env,
+            &BytesN::from_array(env, &key.verifying_key().to_bytes()),
+        );
+        key
+    }
+
+    fn sign(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+        let bytes: std::vec::Vec<u8> = message.iter().collect();
+        BytesN::from_array(env, &key.sign(&bytes).to_bytes())
+    }
+
+    #[test]
+    fn rejects_before_checking_signature() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+        let signature = BytesN::from_array(&env, &[0; 64]);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            assert_eq!(
+                redeem(&env, &player, 10, 0, 1, 100, &signature),
+                Err(GameError::Unauthorized)
+            );
+
+            set_signer(&env, &BytesN::from_array(&env, &[7; 32]));
+            env.ledger().set_timestamp(101);
+            assert_eq!(
+                redeem(&env, &player, 10, 0, 1, 100, &signature),
+                Err(GameError::Expired)
+            );
+
+            env.storage()
+                .persistent()
+                .set(&DataKey::Used(player.clone(), 1), &true);
+            assert!(is_used(&env, &player, 1));
+            assert_eq!(
+                redeem(&env, &player, 10, 0, 1, 200, &signature),
+                Err(GameError::AlreadyClaimed)
+            );
+        });
+    }
+
+    #[test]
+    fn redeems_signed_voucher_once() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            let key = register_signer(&env);
+            let signature = sign(&env, &key, &message(&env, &player, 10, 5, 1, 100));
+
+            assert_eq!(redeem(&env, &player, 10, 5, 1, 100, &signature), Ok(()));
+            assert!(is_used(&env, &player, 1));
+            assert_eq!(
+                redeem(&env, &player, 10, 5, 1, 100, &signature),
+                Err(GameError::AlreadyClaimed)
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn tampered_voucher_traps() {
+        let env = Env::default();
+        let player = Address::generate(&env);
+
+        let contract_id = env.register_contract(None, Dummy);
+        env.as_contract(&contract_id, || {
+            let key = register_signer(&env);
+            let signature = sign(&env, &key, &message(&env, &player, 10, 5, 1, 100));
+
+            let _ = redeem(&env, &player, 1_000, 5, 1, 100, &signature);
+        });
+    }
+}
@@ -63,6 +63,7 @@ impl TeaNftContract {
         let raw_id = Enumerable::sequential_mint(&env, &to);
         let id = u64::from(raw_id);
         storage::set_metadata(&env, id, &tea_metadata);
+        storage::set_minted_at(&env, id);
         env.events().publish(
             ("tea_minted",),
             (to, id, tea_metadata.rarity, tea_metadata.level),
@@ -70,6 +71,32 @@ impl TeaNftContract {
         id
     }
 
+    /// Mints a token that can never change owner, such as an achievement badge.
+    pub fn mint_soulbound(
+        env: Env,
+        caller: Address,
+        to: Address,
+        tea_metadata: TeaMetadata,
+    ) -> u64 {
+        admin::require_operator_or_admin(&env, &caller);
+        let raw_id = Enumerable::sequential_mint(&env, &to);
+        let id = u64::from(raw_id);
+        storage::set_metadata(&env, id, &tea_metadata);
+        storage::set_soulbound(&env, id);
+        storage::set_minted_at(&env, id);
+        env.events().publish(("soulbound_minted",), (to, id));
+        id
+    }
+
+    pub fn is_soulbound(env: Env, token_id: u64) -> bool {
+        storage::is_soulbound(&env, token_id)
+    }
+
+    /// Ledger time the token was minted. Tokens minted before this was recorded have none.
+    pub fn minted_at(env: Env, token_id: u64) -> Option<u64> {
+        storage::minted_at(&env, token_id)
+    }
+
     pub fn get_metadata(env: Env, token_id: u64) -> TeaMetadata {
         storage::get_metadata(&env, token_id)
     }
@@ -99,21 +126,48 @@ impl TeaNftContract {
         env.events().publish(("lineage_set",), (token_id,));
     }
 
+    /// Burns a token. Only the operator or admin may burn a soulbound token.
     pub fn burn_token(env: Env, caller: Address, owner: Address, token_id: u64) {
         if caller != owner {
             admin::require_operator_or_admin(&env, &caller);
+        } else {
+            ensure_burnable(&env, as_nft_id(token_id));
         }
         owner.require_auth();
         Base::burn(&env, &owner, as_nft_id(token_id));
         storage::remove_metadata(&env, token_id);
+        storage::remove_soulbound(&env, token_id);
+        storage::remove_minted_at(&env, token_id);
         env.events().publish(("tea_burned",), (owner, token_id));
     }
 }
 
+fn ensure_transferable(env: &Env, token_id: u32) {
+    if storage::is_soulbound(env, u64::from(token_id)) {
+        panic!("soulbound token cannot be transferred");
+    }
+}
+
+fn ensure_burnable(env: &Env, token_id: u32) {
+    if storage::is_soulbound(env, u64::from(token_id)) {
+        panic!("soulbound token cannot be burned");
+    }
+}
+
 #[default_impl]
 #[contractimpl]
 impl NonFungibleToken for TeaNftContract {
     type ContractType = Enumerable;
+
+    fn transfer(e: &Env, from: Address, to: Address, token_id: u32) {
+        ensure_transferable(e, token_id);
+        Enumerable::transfer(e, &from, &to, token_id);
+    }
+
+    fn transfer_from(e: &Env, spender: Address, from: Address, to: Address, token_id: u32) {
+        ensure_transferable(e, token_id);
+        Enumerable::transfer_from(e, &spender, &from, &to, token_id);
+    }
 }
 
 #[default_impl]
@@ -122,4 +176,14 @@ impl NonFungibleEnumerable for TeaNftContract {}
 
 #[default_impl]
 #[contractimpl]
-impl NonFungibleBurnable for TeaNftContract {}
+impl NonFungibleBurnable for TeaNftContract {
+    fn burn(e: &Env, from: Address, token_id: u32) {
+        ensure_burnable(e, token_id);
+        Enumerable::burn(e, &from, token_id);
+    }
+
+    fn burn_from(e: &Env, spender: Address, from: Address, token_id: u32) {
+        ensure_burnable(e, token_id);
+        Enumerable::burn_from(e, &spender, &from, token_id);
+    }
+}
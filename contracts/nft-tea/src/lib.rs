@@ -58,6 +58,7 @@ mod tests {
         client.set_game_operator(&operator);
         let metadata = sample_metadata(&env);
         let token_id = client.mint(&operator, &owner, &metadata);
+        assert_eq!(client.minted_at(&token_id), Some(env.ledger().timestamp()));
 
         let metadata = client.get_metadata(&token_id);
         assert_eq!(metadata.display_name, String::from_str(&env, "Lunar Assam"));
@@ -66,4 +67,31 @@ mod tests {
         let updated = client.get_metadata(&token_id);
         assert_eq!(updated.level, 5);
     }
+
+    #[test]
+    fn soulbound_tokens_reject_transfers() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let operator = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let other = Address::generate(&env);
+        let client = init_client(&env, &admin);
+
+        client.set_game_operator(&operator);
+        let tea = client.mint(&operator, &owner, &sample_metadata(&env));
+        let badge = client.mint_soulbound(&operator, &owner, &sample_metadata(&env));
+        assert!(client.is_soulbound(&badge));
+        assert!(!client.is_soulbound(&tea));
+
+        client.transfer(&owner, &other, &(tea as u32));
+        assert_eq!(client.owner_of(&(tea as u32)), other);
+        assert!(client
+            .try_transfer(&owner, &other, &(badge as u32))
+            .is_err());
+        assert_eq!(client.owner_of(&(badge as u32)), owner);
+        assert!(client.try_burn(&owner, &(badge as u32)).is_err());
+        assert!(client.try_burn_token(&owner, &owner, &badge).is_err());
+    }
 }
@@ -6,6 +6,8 @@ use crate::tea::TeaMetadata;
 #[contracttype]
 pub enum DataKey {
     Token(u64),
+    Soulbound(u64),
+    MintedAt(u64),
 }
 
 pub fn set_metadata(env: &Env, token_id: u64, metadata: &TeaMetadata) {
@@ -24,3 +26,39 @@ pub fn get_metadata(env: &Env, token_id: u64) -> TeaMetadata {
 pub fn remove_metadata(env: &Env, token_id: u64) {
     env.storage().persistent().remove(&DataKey::Token(token_id));
 }
+
+pub fn set_soulbound(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Soulbound(token_id), &true);
+}
+
+pub fn is_soulbound(env: &Env, token_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Soulbound(token_id))
+}
+
+pub fn set_minted_at(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::MintedAt(token_id), &env.ledger().timestamp());
+}
+
+pub fn minted_at(env: &Env, token_id: u64) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::MintedAt(token_id))
+}
+
+pub fn remove_minted_at(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::MintedAt(token_id));
+}
+
+pub fn remove_soulbound(env: &Env, token_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Soulbound(token_id));
+}